@@ -0,0 +1,4 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" tiledversion="1.10.2" name="tiles_sheet" tilewidth="70" tileheight="70" tilecount="156" columns="12">
+ <image source="../sprites/tiles_sheet.png" width="888" height="910"/>
+</tileset>
@@ -0,0 +1,292 @@
+use std::time::Duration;
+
+use bevy::{ecs::system::CommandQueue, prelude::*, time::TimeUpdateStrategy};
+use bevy_rapier2d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    level::{spawn_level_entities, GrapplePoint, Level},
+    physics::{Interpolated, PhysicsPlugin, FIXED_TIMESTEP},
+    player::{
+        input::InputAction, movement::velocity::KinematicVelocity, HeadlessPlayerPlugin, Player,
+        PlayerSet,
+    },
+};
+
+/// The game without a window, renderer or input devices, for testing movement. Every
+/// [`Simulation::step`] runs exactly one fixed update, and the input of the player comes from
+/// [`Simulation::press`], [`Simulation::release`] and [`Simulation::run`] (or an [`InputScript`])
+pub struct Simulation {
+    pub app: App,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulation {
+    /// Spawns the player at the same place as the game does, with nothing to stand on
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            PhysicsPlugin,
+            HeadlessPlayerPlugin,
+        ))
+        // Rapier needs meshes for its async colliders
+        .add_asset::<Mesh>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            FIXED_TIMESTEP,
+        )))
+        .init_resource::<ScriptedInput>()
+        .init_resource::<FixedUpdates>()
+        .add_systems(Startup, init.in_set(PlayerSet::Input))
+        .add_systems(PreUpdate, apply_scripted_input)
+        .add_systems(FixedUpdate, count_fixed_updates);
+
+        // The first frame runs the startup systems, no time passes during it
+        app.update();
+        Self { app }
+    }
+
+    pub fn player(&mut self) -> Entity {
+        self.app
+            .world
+            .query_filtered::<Entity, With<Player>>()
+            .single(&self.app.world)
+    }
+
+    /// A component of the player, like its state or one of its movement components
+    pub fn get<T: Component + Clone>(&mut self) -> Option<T> {
+        let player = self.player();
+        self.app.world.get::<T>(player).cloned()
+    }
+
+    /// Where the physics has the player, not where it is rendered
+    pub fn position(&mut self) -> Vec2 {
+        let player = self.player();
+        let entity = self.app.world.entity(player);
+        match entity.get::<Interpolated>() {
+            Some(i) => i.current().truncate(),
+            None => entity.get::<Transform>().unwrap().translation.truncate(),
+        }
+    }
+
+    pub fn velocity(&mut self) -> Vec2 {
+        self.get::<KinematicVelocity>()
+            .map(Vec2::from)
+            .unwrap_or_default()
+    }
+
+    pub fn set_position(&mut self, position: Vec2) {
+        let player = self.player();
+        let mut transform = self.app.world.get_mut::<Transform>(player).unwrap();
+        transform.translation = position.extend(transform.translation.z);
+    }
+
+    pub fn set_velocity(&mut self, velocity: Vec2) {
+        let player = self.player();
+        if let Some(mut vel) = self.app.world.get_mut::<KinematicVelocity>(player) {
+            *vel = velocity.into();
+        }
+    }
+
+    /// A solid rectangle centered on `position`
+    pub fn spawn_solid(&mut self, position: Vec2, size: Vec2) -> Entity {
+        self.app
+            .world
+            .spawn((
+                TransformBundle::from_transform(Transform::from_translation(position.extend(0f32))),
+                Collider::cuboid(size.x / 2f32, size.y / 2f32),
+                Name::from("Solid"),
+            ))
+            .id()
+    }
+
+    /// Spawns the entities of `level` the way the game does, and moves the player to its spawn
+    pub fn spawn_level(&mut self, level: &Level) {
+        let asset_server = self.app.world.resource::<AssetServer>().clone();
+        let mut queue = CommandQueue::default();
+        let mut cmd = Commands::new(&mut queue, &self.app.world);
+        spawn_level_entities(&mut cmd, &asset_server, level);
+        queue.apply(&mut self.app.world);
+        self.set_position(level.player_spawn);
+    }
+
+    pub fn spawn_grapple_point(&mut self, position: Vec2) -> Entity {
+        self.app
+            .world
+            .spawn((
+                TransformBundle::from_transform(Transform::from_translation(position.extend(0f32))),
+                GrapplePoint,
+                Name::from("Grapple Point"),
+            ))
+            .id()
+    }
+
+    /// Holds `action` down until it is released
+    pub fn press(&mut self, action: InputAction) {
+        let mut input = self.app.world.resource_mut::<ScriptedInput>();
+        if !input.pressed.contains(&action) {
+            input.pressed.push(action);
+        }
+    }
+
+    pub fn release(&mut self, action: InputAction) {
+        let mut input = self.app.world.resource_mut::<ScriptedInput>();
+        input.pressed.retain(|a| *a != action);
+    }
+
+    /// Holds run at `value`, from -1 (left) to 1 (right)
+    pub fn run(&mut self, value: f32) {
+        self.app.world.resource_mut::<ScriptedInput>().run = value;
+    }
+
+    /// Runs one fixed update
+    pub fn step(&mut self) {
+        let before = self.app.world.resource::<FixedUpdates>().0;
+        self.app.update();
+        let ran = self.app.world.resource::<FixedUpdates>().0 - before;
+        assert_eq!(ran, 1, "a step should run exactly one fixed update");
+    }
+
+    pub fn steps(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// Steps until `done` is true, returns how many frames it took or `None` if it took longer
+    /// than `max_frames`
+    pub fn step_until(
+        &mut self,
+        max_frames: usize,
+        mut done: impl FnMut(&mut Self) -> bool,
+    ) -> Option<usize> {
+        for frame in 0..=max_frames {
+            if done(self) {
+                return Some(frame);
+            }
+            if frame < max_frames {
+                self.step();
+            }
+        }
+        None
+    }
+
+    /// Plays the script, and returns where the player was after every frame of it
+    pub fn play(&mut self, script: &InputScript) -> Vec<Vec2> {
+        let mut positions = Vec::new();
+        for step in script.0.iter() {
+            match *step {
+                ScriptStep::Press(action) => self.press(action),
+                ScriptStep::Release(action) => self.release(action),
+                ScriptStep::Run(value) => self.run(value),
+                ScriptStep::Wait(frames) => {
+                    for _ in 0..frames {
+                        self.step();
+                        positions.push(self.position());
+                    }
+                }
+            }
+        }
+        positions
+    }
+}
+
+/// A list of inputs and how many frames to wait between them, played by [`Simulation::play`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputScript(pub Vec<ScriptStep>);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScriptStep {
+    Press(InputAction),
+    Release(InputAction),
+    Run(f32),
+    Wait(usize),
+}
+
+impl InputScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn press(self, action: InputAction) -> Self {
+        self.push(ScriptStep::Press(action))
+    }
+
+    pub fn release(self, action: InputAction) -> Self {
+        self.push(ScriptStep::Release(action))
+    }
+
+    pub fn run(self, value: f32) -> Self {
+        self.push(ScriptStep::Run(value))
+    }
+
+    pub fn wait(self, frames: usize) -> Self {
+        self.push(ScriptStep::Wait(frames))
+    }
+
+    /// Presses `action` for `frames` frames, and then releases it
+    pub fn hold(self, action: InputAction, frames: usize) -> Self {
+        self.press(action).wait(frames).release(action)
+    }
+
+    fn push(mut self, step: ScriptStep) -> Self {
+        self.0.push(step);
+        self
+    }
+}
+
+/// What the script is currently holding down
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+struct ScriptedInput {
+    pressed: Vec<InputAction>,
+    run: f32,
+}
+
+/// How many fixed updates have run
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+struct FixedUpdates(usize);
+
+fn count_fixed_updates(mut count: ResMut<FixedUpdates>) {
+    count.0 += 1;
+}
+
+fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>) {
+    cmd.entity(player_query.single()).insert((
+        ActionState::<InputAction>::default(),
+        // Wall detection uses the size of the sprite
+        TextureAtlasSprite {
+            custom_size: Some((25f32, 50f32).into()),
+            ..Default::default()
+        },
+    ));
+}
+
+/// Does what the input manager would do with the scripted input
+fn apply_scripted_input(
+    mut query: Query<&mut ActionState<InputAction>, With<Player>>,
+    input: Res<ScriptedInput>,
+    time: Res<Time>,
+) {
+    let now = time.last_update().unwrap_or_else(|| time.startup());
+    for mut actions in query.iter_mut() {
+        actions.tick(now, now - time.delta());
+        for action in InputAction::variants() {
+            let pressed = match action {
+                InputAction::Run => input.run != 0f32,
+                _ => input.pressed.contains(&action),
+            };
+            match (pressed, actions.pressed(action)) {
+                (true, false) => actions.press(action),
+                (false, true) => actions.release(action),
+                _ => {}
+            }
+        }
+        actions.action_data_mut(InputAction::Run).value = input.run;
+    }
+}
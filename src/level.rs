@@ -1,100 +1,401 @@
-use bevy::prelude::*;
+use std::path::{Component, Path, PathBuf};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
 use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+use crate::player::Player;
+
+pub mod ldtk;
+pub mod tiled;
+pub mod tilemap;
+
+use ldtk::*;
+use tiled::*;
+use tilemap::*;
+
+/// The level that is loaded at startup, unless another one is passed as the first argument
+/// (`cargo run -- levels/example.tmx`)
+pub const START_LEVEL: &str = "levels/start.level.ron";
+
+const SOLID_COLOR: (u8, u8, u8) = (205, 255, 150);
+const GRAPPLE_POINT_COLOR: (u8, u8, u8) = (255, 75, 125);
+const GRAPPLE_POINT_SIZE: f32 = 50f32;
 
 pub(super) struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, init);
+        app.add_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .init_asset_loader::<TiledLoader>()
+            .init_asset_loader::<LdtkLoader>()
+            .init_resource::<CurrentLevel>()
+            .add_systems(Update, spawn_level)
+            .register_type::<Checkpoint>()
+            .register_type::<Room>();
     }
 }
 
-fn init(mut cmd: Commands) {
-    cmd.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                color: Color::rgb_u8(205, 255, 150),
-                custom_size: Some(Vec2::new(500f32, 25f32)),
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(0f32, -50f32, 0f32),
-            ..Default::default()
-        },
-        Collider::cuboid(250f32, 12.75f32),
-        Name::from("Platform"),
-    ));
-
-    cmd.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                color: Color::rgb_u8(205, 255, 150),
-                custom_size: Some(Vec2::new(25f32, 1000f32)),
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(-300f32, 0f32, 0f32),
-            ..Default::default()
-        },
-        Collider::cuboid(12.75f32, 500f32),
-        Name::from("Wall"),
-    ));
-
-    cmd.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                color: Color::rgb_u8(205, 255, 150),
-                custom_size: Some(Vec2::new(25f32, 700f32)),
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(250f32, 150f32, 0f32),
-            ..Default::default()
-        },
-        Collider::cuboid(12.75f32, 350f32),
-        Name::from("Wall #2"),
-    ));
-
-    cmd.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                color: Color::rgb_u8(205, 255, 150),
-                custom_size: Some(Vec2::new(30f32, 100f32)),
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(220f32, -10f32, 0f32)
-                .with_rotation(Quat::from_rotation_z(2.5f32)),
-            ..Default::default()
-        },
-        Collider::cuboid(12.75f32, 350f32),
-        Name::from("Wall #3"),
-    ));
-
-    cmd.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                color: Color::rgb_u8(255, 75, 125),
-                custom_size: Some(Vec2::new(50f32, 50f32)),
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(550f32, 100f32, 0f32),
-            ..Default::default()
-        },
-        GrapplePoint,
-        Name::from("Grapple Point"),
-    ));
-
-    cmd.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                color: Color::rgb_u8(255, 75, 125),
-                custom_size: Some(Vec2::new(50f32, 50f32)),
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(200f32, 300f32, 0f32),
-            ..Default::default()
-        },
-        GrapplePoint,
-        Name::from("Grapple Point #2"),
-    ));
-}
-
 #[derive(Component)]
 pub struct GrapplePoint;
+
+#[derive(Component, Deserialize, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+pub struct Checkpoint {
+    /// Where in the level the checkpoint is, higher is further along
+    #[serde(default)]
+    pub order: i32,
+}
+
+/// A part of a level that the camera is kept inside of, LDtk levels become rooms
+#[derive(Component, Deserialize, Reflect, Clone, Debug, PartialEq)]
+pub struct Room {
+    #[serde(default = "default_room_name")]
+    pub name: String,
+    pub min: Vec2,
+    pub max: Vec2,
+    /// World space vertices for rooms that aren't rectangles, they should be inside `min` and
+    /// `max`
+    #[serde(default)]
+    pub polygon: Option<Vec<Vec2>>,
+}
+
+impl Room {
+    pub fn rect(&self) -> Rect {
+        Rect::from_corners(self.min, self.max)
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        if !self.rect().contains(point) {
+            return false;
+        }
+        match &self.polygon {
+            Some(vertices) => polygon_contains(vertices, point),
+            None => true,
+        }
+    }
+
+    /// `point` if it is inside the room, otherwise the closest point on the edge of the room
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        if self.contains(point) {
+            return point;
+        }
+        match &self.polygon {
+            Some(vertices) => (0..vertices.len())
+                .map(|i| {
+                    let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+                    let t = (point - a).dot(b - a) / (b - a).length_squared().max(f32::EPSILON);
+                    a + (b - a) * t.clamp(0f32, 1f32)
+                })
+                .min_by(|a, b| {
+                    a.distance_squared(point)
+                        .total_cmp(&b.distance_squared(point))
+                })
+                .unwrap_or(point),
+            None => point.clamp(self.min, self.max),
+        }
+    }
+}
+
+/// Even-odd rule, so it works with concave polygons too
+fn polygon_contains(vertices: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for i in 0..vertices.len() {
+        let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// The level that is currently spawned (or about to be spawned once it has loaded)
+#[derive(Resource, Clone, Debug)]
+pub struct CurrentLevel(pub Handle<Level>);
+
+impl FromWorld for CurrentLevel {
+    fn from_world(world: &mut World) -> Self {
+        let path = std::env::args()
+            .nth(1)
+            .unwrap_or_else(|| START_LEVEL.to_string());
+        Self(world.resource::<AssetServer>().load(path))
+    }
+}
+
+/// Every entity spawned from a level file is a child of the entity with this component, so the
+/// whole level can be despawned at once
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct LevelRoot;
+
+/// A level file (`*.level.ron`, a Tiled map loaded by [`TiledLoader`] or an LDtk project loaded
+/// by [`LdtkLoader`])
+#[derive(TypeUuid, TypePath, Deserialize, Default, Clone, Debug, PartialEq)]
+#[uuid = "4d6f5c1e-2a7b-4c0f-9b1e-6a3d2f8e9c41"]
+pub struct Level {
+    pub player_spawn: Vec2,
+    #[serde(default)]
+    pub solids: Vec<Solid>,
+    #[serde(default)]
+    pub grapple_points: Vec<GrapplePointData>,
+    #[serde(default)]
+    pub tilemaps: Vec<TileMapData>,
+    #[serde(default)]
+    pub polygons: Vec<PolygonSolid>,
+    #[serde(default)]
+    pub checkpoints: Vec<CheckpointData>,
+    #[serde(default)]
+    pub rooms: Vec<Room>,
+}
+
+/// A solid rectangle, the collider is the same size as the sprite unless `collider_size` is set
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Solid {
+    #[serde(default = "default_solid_name")]
+    pub name: String,
+    pub position: Vec2,
+    pub size: Vec2,
+    #[serde(default)]
+    pub collider_size: Option<Vec2>,
+    /// Rotation around the z axis in radians
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default)]
+    pub color: Option<(u8, u8, u8)>,
+    /// Only spawn the collider
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+impl Solid {
+    pub fn collider(&self) -> Collider {
+        let size = self.collider_size.unwrap_or(self.size);
+        Collider::cuboid(size.x / 2f32, size.y / 2f32)
+    }
+}
+
+/// A solid with any shape, only the collider is spawned
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct PolygonSolid {
+    #[serde(default = "default_solid_name")]
+    pub name: String,
+    pub position: Vec2,
+    /// Rotation around the z axis in radians
+    #[serde(default)]
+    pub rotation: f32,
+    /// Relative to `position`
+    pub vertices: Vec<Vec2>,
+    /// If false the vertices are a line strip instead of a polygon
+    #[serde(default = "default_closed")]
+    pub closed: bool,
+}
+
+impl PolygonSolid {
+    pub fn collider(&self) -> Collider {
+        if !self.closed {
+            return Collider::polyline(self.vertices.clone(), None);
+        }
+
+        let len = self.vertices.len() as u32;
+        let indices = (0..len)
+            .map(|i| [i, (i + 1) % len])
+            .collect::<Vec<[u32; 2]>>();
+        Collider::convex_decomposition(&self.vertices, &indices)
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct GrapplePointData {
+    #[serde(default = "default_grapple_point_name")]
+    pub name: String,
+    pub position: Vec2,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct CheckpointData {
+    #[serde(default = "default_checkpoint_name")]
+    pub name: String,
+    pub position: Vec2,
+    pub size: Vec2,
+    #[serde(flatten)]
+    pub checkpoint: Checkpoint,
+}
+
+fn default_solid_name() -> String {
+    "Solid".to_string()
+}
+
+fn default_grapple_point_name() -> String {
+    "Grapple Point".to_string()
+}
+
+fn default_checkpoint_name() -> String {
+    "Checkpoint".to_string()
+}
+
+fn default_room_name() -> String {
+    "Room".to_string()
+}
+
+fn default_closed() -> bool {
+    true
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let level = ron::de::from_bytes::<Level>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// Spawns the current level when it has loaded, and respawns it if it is modified
+fn spawn_level(
+    mut cmd: Commands,
+    mut events: EventReader<AssetEvent<Level>>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+    root_query: Query<Entity, With<LevelRoot>>,
+    levels: Res<Assets<Level>>,
+    current: Res<CurrentLevel>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        if *handle != current.0 {
+            continue;
+        }
+        let level = match levels.get(handle) {
+            Some(l) => l,
+            None => continue,
+        };
+
+        for root in root_query.iter() {
+            cmd.entity(root).despawn_recursive();
+        }
+        spawn_level_entities(&mut cmd, &asset_server, level);
+
+        for mut transform in player_query.iter_mut() {
+            transform.translation = level.player_spawn.extend(transform.translation.z);
+        }
+    }
+}
+
+pub(crate) fn spawn_level_entities(
+    cmd: &mut Commands,
+    asset_server: &AssetServer,
+    level: &Level,
+) {
+    let root = cmd
+        .spawn((LevelRoot, SpatialBundle::default(), Name::from("Level")))
+        .id();
+
+    for (i, data) in level.tilemaps.iter().enumerate() {
+        let z = TILEMAP_Z + i as f32 * TILEMAP_Z_STEP;
+        spawn_tilemap(cmd, asset_server, root, data, z);
+    }
+
+    cmd.entity(root).with_children(|parent| {
+        for solid in level.solids.iter() {
+            let (r, g, b) = solid.color.unwrap_or(SOLID_COLOR);
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb_u8(r, g, b),
+                        custom_size: Some(solid.size),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(solid.position.extend(0f32))
+                        .with_rotation(Quat::from_rotation_z(solid.rotation)),
+                    visibility: match solid.hidden {
+                        true => Visibility::Hidden,
+                        false => Visibility::Inherited,
+                    },
+                    ..Default::default()
+                },
+                solid.collider(),
+                Name::from(solid.name.as_str()),
+            ));
+        }
+
+        for polygon in level.polygons.iter() {
+            parent.spawn((
+                TransformBundle::from_transform(
+                    Transform::from_translation(polygon.position.extend(0f32))
+                        .with_rotation(Quat::from_rotation_z(polygon.rotation)),
+                ),
+                polygon.collider(),
+                Name::from(polygon.name.as_str()),
+            ));
+        }
+
+        let (r, g, b) = GRAPPLE_POINT_COLOR;
+        for point in level.grapple_points.iter() {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb_u8(r, g, b),
+                        custom_size: Some(Vec2::splat(GRAPPLE_POINT_SIZE)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(point.position.extend(0f32)),
+                    ..Default::default()
+                },
+                GrapplePoint,
+                Name::from(point.name.as_str()),
+            ));
+        }
+
+        for checkpoint in level.checkpoints.iter() {
+            parent.spawn((
+                TransformBundle::from_transform(Transform::from_translation(
+                    checkpoint.position.extend(0f32),
+                )),
+                Collider::cuboid(checkpoint.size.x / 2f32, checkpoint.size.y / 2f32),
+                Sensor,
+                checkpoint.checkpoint,
+                Name::from(checkpoint.name.as_str()),
+            ));
+        }
+
+        for room in level.rooms.iter() {
+            parent.spawn((room.clone(), Name::from(room.name.as_str())));
+        }
+    });
+}
+
+/// Joins a path relative to a level file onto the directory of that file, resolving `..`
+fn resolve_path(dir: &Path, relative: &str) -> PathBuf {
+    let mut path = PathBuf::new();
+    for component in dir.join(relative).components() {
+        match component {
+            Component::ParentDir => {
+                path.pop();
+            }
+            Component::CurDir => {}
+            c => path.push(c),
+        }
+    }
+    path
+}
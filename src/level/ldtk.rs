@@ -0,0 +1,381 @@
+use std::path::Path;
+
+use bevy::{
+    asset::{AssetLoader, Error, LoadContext, LoadedAsset},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{Map, Value};
+
+use super::{resolve_path, tilemap::*, Level, Room, Solid};
+
+/// Loads LDtk projects (`*.ldtk`, levels may be saved in separate `*.ldtkl` files) as a [`Level`]
+///
+/// Every LDtk level becomes a [`Room`]. Non zero IntGrid values are solid, tile and auto-layer
+/// tiles are rendered as tilemaps, and the entities `PlayerSpawn`, `GrapplePoint` and
+/// `Checkpoint` are spawned with their custom fields deserialized into the level data (so a
+/// `name` field on a grapple point becomes [`super::GrapplePointData::name`])
+#[derive(Default)]
+pub struct LdtkLoader;
+
+impl AssetLoader for LdtkLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let mut project = serde_json::from_slice::<Project>(bytes)?;
+            let dir = load_context
+                .path()
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+
+            for level in project.levels.iter_mut() {
+                if let Some(path) = &level.external_rel_path {
+                    let bytes = load_context
+                        .read_asset_bytes(resolve_path(&dir, path))
+                        .await?;
+                    *level = serde_json::from_slice::<LdtkLevel>(&bytes)?;
+                }
+            }
+
+            let level = convert_project(&project, &dir)?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Project {
+    defs: Defs,
+    /// `None` in projects with multiple worlds, which are treated as free
+    world_layout: Option<WorldLayout>,
+    levels: Vec<LdtkLevel>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+enum WorldLayout {
+    Free,
+    GridVania,
+    /// The world position of levels is -1, they are placed next to each other in order
+    LinearHorizontal,
+    LinearVertical,
+}
+
+#[derive(Deserialize)]
+struct Defs {
+    tilesets: Vec<TilesetDef>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TilesetDef {
+    uid: i64,
+    rel_path: Option<String>,
+    tile_grid_size: f32,
+    spacing: f32,
+    padding: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLevel {
+    identifier: String,
+    world_x: f32,
+    world_y: f32,
+    px_wid: f32,
+    px_hei: f32,
+    external_rel_path: Option<String>,
+    layer_instances: Option<Vec<LayerInstance>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayerInstance {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__type")]
+    kind: String,
+    #[serde(rename = "__cWid")]
+    c_wid: u32,
+    #[serde(rename = "__cHei")]
+    c_hei: u32,
+    #[serde(rename = "__gridSize")]
+    grid_size: f32,
+    #[serde(rename = "__tilesetDefUid")]
+    tileset_def_uid: Option<i64>,
+    #[serde(rename = "__pxTotalOffsetX")]
+    px_total_offset_x: f32,
+    #[serde(rename = "__pxTotalOffsetY")]
+    px_total_offset_y: f32,
+    visible: bool,
+    #[serde(default)]
+    int_grid_csv: Vec<i64>,
+    #[serde(default)]
+    auto_layer_tiles: Vec<TileInstance>,
+    #[serde(default)]
+    grid_tiles: Vec<TileInstance>,
+    #[serde(default)]
+    entity_instances: Vec<EntityInstance>,
+}
+
+#[derive(Deserialize)]
+struct TileInstance {
+    px: [f32; 2],
+    /// Bit 0 is x flip, bit 1 is y flip
+    f: u8,
+    t: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EntityInstance {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__pivot")]
+    pivot: [f32; 2],
+    px: [f32; 2],
+    width: f32,
+    height: f32,
+    field_instances: Vec<FieldInstance>,
+}
+
+#[derive(Deserialize)]
+struct FieldInstance {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__value")]
+    value: Value,
+}
+
+fn convert_project(project: &Project, dir: &Path) -> Result<Level, Error> {
+    let mut level = Level::default();
+    let mut spawn = None;
+    // Where the next level goes in linear layouts, in LDtk coordinates
+    let mut next = Vec2::ZERO;
+
+    for ldtk_level in project.levels.iter() {
+        let world_pos = match project.world_layout {
+            Some(WorldLayout::LinearHorizontal) => {
+                let pos = next;
+                next.x += ldtk_level.px_wid;
+                pos
+            }
+            Some(WorldLayout::LinearVertical) => {
+                let pos = next;
+                next.y += ldtk_level.px_hei;
+                pos
+            }
+            _ => Vec2::new(ldtk_level.world_x, ldtk_level.world_y),
+        };
+        // LDtk has y pointing down, the world has y pointing up
+        let top_left = world_pos * Vec2::new(1f32, -1f32);
+        level.rooms.push(Room {
+            name: ldtk_level.identifier.clone(),
+            min: top_left - Vec2::new(0f32, ldtk_level.px_hei),
+            max: top_left + Vec2::new(ldtk_level.px_wid, 0f32),
+            polygon: None,
+        });
+
+        let layers = match &ldtk_level.layer_instances {
+            Some(l) => l,
+            None => continue,
+        };
+        // Layers are stored top-most first, tilemaps are drawn in order
+        for layer in layers.iter().rev().filter(|l| l.visible) {
+            let offset = Vec2::new(layer.px_total_offset_x, -layer.px_total_offset_y);
+            let layer_top_left = top_left + offset;
+
+            if layer.kind == "IntGrid" {
+                level
+                    .solids
+                    .extend(int_grid_solids(ldtk_level, layer, layer_top_left));
+            }
+            if layer.kind == "Entities" {
+                for entity in layer.entity_instances.iter() {
+                    convert_entity(entity, layer_top_left, &mut level, &mut spawn)?;
+                }
+            }
+            level.tilemaps.extend(convert_tiles(
+                project,
+                dir,
+                ldtk_level,
+                layer,
+                layer_top_left,
+            )?);
+        }
+    }
+
+    level.player_spawn = match spawn {
+        Some(s) => s,
+        None => return Err(Error::msg("Project has no PlayerSpawn entity")),
+    };
+    Ok(level)
+}
+
+fn int_grid_solids(
+    ldtk_level: &LdtkLevel,
+    layer: &LayerInstance,
+    top_left: Vec2,
+) -> impl Iterator<Item = Solid> {
+    let size = UVec2::new(layer.c_wid, layer.c_hei);
+    // IntGrid rows go top to bottom, merge_rects goes bottom to top
+    let mut cells = vec![false; (size.x * size.y) as usize];
+    for (i, value) in layer.int_grid_csv.iter().enumerate() {
+        let y = match size.y.checked_sub(i as u32 / size.x + 1) {
+            Some(y) => y,
+            None => {
+                warn!(
+                    "IntGrid layer {} of {} has more values than cells",
+                    layer.identifier, ldtk_level.identifier
+                );
+                break;
+            }
+        };
+        cells[(y * size.x + i as u32 % size.x) as usize] = *value != 0;
+    }
+
+    let grid_size = layer.grid_size;
+    let bottom_left = top_left - Vec2::new(0f32, size.y as f32 * grid_size);
+    let name = format!("{} {}", ldtk_level.identifier, layer.identifier);
+    merge_rects(&cells, size).into_iter().map(move |rect| {
+        let size = rect.size().as_vec2() * grid_size;
+        Solid {
+            name: name.clone(),
+            position: bottom_left + rect.min.as_vec2() * grid_size + size / 2f32,
+            size,
+            collider_size: None,
+            rotation: 0f32,
+            color: None,
+            hidden: true,
+        }
+    })
+}
+
+/// Tiles that overlap (which auto-layers often do) are put in extra tilemaps on top
+fn convert_tiles(
+    project: &Project,
+    dir: &Path,
+    ldtk_level: &LdtkLevel,
+    layer: &LayerInstance,
+    top_left: Vec2,
+) -> Result<Vec<TileMapData>, Error> {
+    let tiles = layer.auto_layer_tiles.iter().chain(layer.grid_tiles.iter());
+    let tileset = match layer.tileset_def_uid {
+        Some(uid) => match project.defs.tilesets.iter().find(|t| t.uid == uid) {
+            Some(t) => t,
+            None => return Err(Error::msg(format!("No tileset with the uid {uid}"))),
+        },
+        None => return Ok(Vec::new()),
+    };
+    let texture = match &tileset.rel_path {
+        Some(p) => resolve_path(dir, p).to_string_lossy().replace('\\', "/"),
+        None => return Ok(Vec::new()),
+    };
+    if tileset.padding != 0f32 {
+        warn!("Tileset padding is not supported and will be ignored");
+    }
+
+    let size = UVec2::new(layer.c_wid, layer.c_hei);
+    let empty = TileMapData {
+        name: format!("{} {}", ldtk_level.identifier, layer.identifier),
+        texture,
+        texture_tile_size: Vec2::splat(tileset.tile_grid_size),
+        texture_spacing: Vec2::splat(tileset.spacing),
+        tile_size: Vec2::splat(layer.grid_size),
+        origin: top_left - Vec2::new(0f32, size.y as f32 * layer.grid_size),
+        size,
+        tiles: vec![None; (size.x * size.y) as usize],
+    };
+
+    let mut tilemaps: Vec<TileMapData> = Vec::new();
+    for tile in tiles {
+        let cell = Vec2::new(tile.px[0], tile.px[1]) / layer.grid_size;
+        // Casting to u32 saturates, so the subtraction is the only thing that can overflow
+        let y = size
+            .y
+            .checked_sub(cell.y as u32)
+            .and_then(|y| y.checked_sub(1));
+        let (x, y) = match (cell.x as u32, y) {
+            (x, Some(y)) if cell.min_element() >= 0f32 && x < size.x => (x, y),
+            _ => {
+                warn!(
+                    "Tile at {:?} is outside of layer {} of {}",
+                    tile.px, layer.identifier, ldtk_level.identifier
+                );
+                continue;
+            }
+        };
+        let idx = (y * size.x + x) as usize;
+        let data = TileData {
+            index: tile.t,
+            solid: false,
+            flip_x: tile.f & 1 != 0,
+            flip_y: tile.f & 2 != 0,
+            flip_d: false,
+        };
+
+        match tilemaps.iter_mut().find(|t| t.tiles[idx].is_none()) {
+            Some(t) => t.tiles[idx] = Some(data),
+            None => {
+                let mut tilemap = empty.clone();
+                tilemap.tiles[idx] = Some(data);
+                tilemaps.push(tilemap);
+            }
+        }
+    }
+
+    Ok(tilemaps)
+}
+
+fn convert_entity(
+    entity: &EntityInstance,
+    top_left: Vec2,
+    level: &mut Level,
+    spawn: &mut Option<Vec2>,
+) -> Result<(), Error> {
+    let size = Vec2::new(entity.width, entity.height);
+    let pivot = Vec2::new(entity.pivot[0], entity.pivot[1]);
+    let center_px = Vec2::new(entity.px[0], entity.px[1]) + (Vec2::splat(0.5f32) - pivot) * size;
+    let position = top_left + center_px * Vec2::new(1f32, -1f32);
+
+    match entity.identifier.as_str() {
+        "PlayerSpawn" => *spawn = Some(position),
+        "GrapplePoint" => level.grapple_points.push(fields(entity, position, size)?),
+        "Checkpoint" => level.checkpoints.push(fields(entity, position, size)?),
+        _ => warn!("Unknown LDtk entity {}", entity.identifier),
+    }
+    Ok(())
+}
+
+/// Deserializes the custom fields of an entity, along with its `name`, `position` and `size`
+fn fields<T: DeserializeOwned>(
+    entity: &EntityInstance,
+    position: Vec2,
+    size: Vec2,
+) -> Result<T, Error> {
+    let mut map = Map::new();
+    map.insert("name".to_string(), Value::from(entity.identifier.clone()));
+    map.insert(
+        "position".to_string(),
+        Value::from(vec![position.x, position.y]),
+    );
+    map.insert("size".to_string(), Value::from(vec![size.x, size.y]));
+    for field in entity.field_instances.iter() {
+        // Unset optional fields are null, they should use the default instead
+        if !field.value.is_null() {
+            map.insert(field.identifier.clone(), field.value.clone());
+        }
+    }
+
+    Ok(serde_json::from_value::<T>(Value::Object(map))?)
+}
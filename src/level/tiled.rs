@@ -0,0 +1,448 @@
+use std::{
+    collections::HashMap,
+    f32::consts::TAU,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use bevy::{
+    asset::{AssetLoader, Error, LoadContext, LoadedAsset},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use roxmltree::{Document, Node};
+
+use super::{resolve_path, tilemap::*, GrapplePointData, Level, PolygonSolid, Room, Solid};
+
+const FLIP_X: u32 = 0x80000000;
+const FLIP_Y: u32 = 0x40000000;
+const FLIP_D: u32 = 0x20000000;
+/// Only used for hexagonal maps, but it still has to be masked out
+const ROTATE_HEX: u32 = 0x10000000;
+
+const ELLIPSE_SEGMENTS: usize = 16;
+
+/// Loads Tiled maps (`*.tmx`, with inline or external `*.tsx` tilesets) as a [`Level`]
+///
+/// Tile layers become tilemaps, and are only solid if the layer has a bool property `solid` set
+/// to true. In object layers points and rectangles with the class `PlayerSpawn` or
+/// `GrapplePoint` become the player spawn and grapple points, rectangles and polygons with the
+/// class `Room` become rooms, and every other rectangle, polygon, polyline and ellipse becomes a
+/// solid
+#[derive(Default)]
+pub struct TiledLoader;
+
+impl AssetLoader for TiledLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let text = std::str::from_utf8(bytes)?;
+            let dir = load_context
+                .path()
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+
+            // External tilesets have to be read before the map is parsed, since the document
+            // can't be held across an await
+            let mut external = HashMap::new();
+            for source in external_tilesets(text)? {
+                let path = resolve_path(&dir, &source);
+                let tsx = load_context.read_asset_bytes(&path).await?;
+                external.insert(source, (path, String::from_utf8(tsx)?));
+            }
+
+            let level = parse_map(text, &dir, &external)?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx"]
+    }
+}
+
+struct Tileset {
+    first_gid: u32,
+    texture: String,
+    tile_size: Vec2,
+    spacing: Vec2,
+}
+
+fn external_tilesets(text: &str) -> Result<Vec<String>, Error> {
+    let doc = Document::parse(text)?;
+    Ok(doc
+        .root_element()
+        .children()
+        .filter(|n| n.has_tag_name("tileset"))
+        .filter_map(|n| n.attribute("source").map(str::to_string))
+        .collect())
+}
+
+fn parse_map(
+    text: &str,
+    dir: &Path,
+    external: &HashMap<String, (PathBuf, String)>,
+) -> Result<Level, Error> {
+    let doc = Document::parse(text)?;
+    let map = doc.root_element();
+    if map.attribute("orientation") != Some("orthogonal") {
+        return Err(Error::msg("Only orthogonal Tiled maps are supported"));
+    }
+    // Their tile layers are split into chunks
+    if map.attribute("infinite") == Some("1") {
+        return Err(Error::msg(
+            "Infinite Tiled maps are not supported, turn off Infinite in the map properties",
+        ));
+    }
+
+    let size = UVec2::new(attr(map, "width")?, attr(map, "height")?);
+    let tile_size = Vec2::new(attr(map, "tilewidth")?, attr(map, "tileheight")?);
+    let map_height = size.y as f32 * tile_size.y;
+
+    let mut tilesets = Vec::new();
+    for node in map.children().filter(|n| n.has_tag_name("tileset")) {
+        let first_gid = attr(node, "firstgid")?;
+        let tileset = match node.attribute("source") {
+            Some(source) => {
+                let (path, tsx) = match external.get(source) {
+                    Some(t) => t,
+                    None => return Err(Error::msg(format!("Tileset {source} was not read"))),
+                };
+                let doc = Document::parse(tsx)?;
+                let dir = path.parent().unwrap_or(Path::new(""));
+                parse_tileset(doc.root_element(), first_gid, dir)?
+            }
+            None => parse_tileset(node, first_gid, dir)?,
+        };
+        tilesets.push(tileset);
+    }
+    tilesets.sort_by_key(|t| t.first_gid);
+
+    let mut level = Level::default();
+    let mut spawn = None;
+
+    for layer in map.children().filter(|n| n.is_element()) {
+        if layer.attribute("visible") == Some("0") {
+            continue;
+        }
+        let offset = Vec2::new(
+            attr_or(layer, "offsetx", 0f32)?,
+            -attr_or(layer, "offsety", 0f32)?,
+        );
+        match layer.tag_name().name() {
+            "layer" => {
+                let tilemaps = parse_tile_layer(layer, &tilesets, tile_size, offset)?;
+                level.tilemaps.extend(tilemaps);
+            }
+            "objectgroup" => {
+                for object in layer.children().filter(|n| n.has_tag_name("object")) {
+                    let to_world = |p: Vec2| Vec2::new(p.x, map_height - p.y) + offset;
+                    parse_object(object, to_world, &mut level, &mut spawn)?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    level.player_spawn = match spawn {
+        Some(s) => s,
+        None => return Err(Error::msg("Map has no object with the class PlayerSpawn")),
+    };
+    Ok(level)
+}
+
+fn parse_tileset(node: Node, first_gid: u32, dir: &Path) -> Result<Tileset, Error> {
+    let image = match node.children().find(|n| n.has_tag_name("image")) {
+        Some(i) => i,
+        None => {
+            return Err(Error::msg(
+                "Tilesets made from a collection of images are not supported",
+            ))
+        }
+    };
+    if attr_or(node, "margin", 0f32)? != 0f32 {
+        warn!("Tileset margins are not supported and will be ignored");
+    }
+
+    Ok(Tileset {
+        first_gid,
+        texture: resolve_path(dir, attr::<String>(image, "source")?.as_str())
+            .to_string_lossy()
+            .replace('\\', "/"),
+        tile_size: Vec2::new(attr(node, "tilewidth")?, attr(node, "tileheight")?),
+        spacing: Vec2::splat(attr_or(node, "spacing", 0f32)?),
+    })
+}
+
+/// Returns one tilemap per tileset used by the layer
+fn parse_tile_layer(
+    layer: Node,
+    tilesets: &[Tileset],
+    tile_size: Vec2,
+    offset: Vec2,
+) -> Result<Vec<TileMapData>, Error> {
+    let size = UVec2::new(attr(layer, "width")?, attr(layer, "height")?);
+    let name = layer.attribute("name").unwrap_or("Tile Layer");
+    let solid = property(layer, "solid") == Some("true");
+
+    let data = match layer.children().find(|n| n.has_tag_name("data")) {
+        Some(d) => d,
+        None => return Ok(Vec::new()),
+    };
+    let gids = match data.attribute("encoding") {
+        Some("csv") => data
+            .text()
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()?,
+        None => data
+            .children()
+            .filter(|n| n.has_tag_name("tile"))
+            .map(|n| attr_or(n, "gid", 0u32))
+            .collect::<Result<Vec<u32>, _>>()?,
+        Some(encoding) => {
+            return Err(Error::msg(format!(
+                "Tile layer encoding {encoding} is not supported, save the map with CSV tile layers"
+            )))
+        }
+    };
+    if gids.len() != (size.x * size.y) as usize {
+        return Err(Error::msg(format!(
+            "Tile layer {name} has {} tiles, but is {}x{}",
+            gids.len(),
+            size.x,
+            size.y
+        )));
+    }
+
+    let mut tilemaps: HashMap<usize, TileMapData> = HashMap::new();
+    for (i, raw_gid) in gids.into_iter().enumerate() {
+        let gid = raw_gid & !(FLIP_X | FLIP_Y | FLIP_D | ROTATE_HEX);
+        if gid == 0 {
+            continue;
+        }
+        let tileset_idx = match tilesets.iter().rposition(|t| t.first_gid <= gid) {
+            Some(t) => t,
+            None => return Err(Error::msg(format!("No tileset contains the tile {gid}"))),
+        };
+        let tileset = &tilesets[tileset_idx];
+
+        let tilemap = tilemaps.entry(tileset_idx).or_insert_with(|| TileMapData {
+            name: format!("{name} ({})", tileset.texture),
+            texture: tileset.texture.clone(),
+            texture_tile_size: tileset.tile_size,
+            texture_spacing: tileset.spacing,
+            tile_size,
+            origin: offset,
+            size,
+            tiles: vec![None; (size.x * size.y) as usize],
+        });
+
+        // Tiled goes top to bottom, tilemaps go bottom to top
+        let (x, y) = (i as u32 % size.x, size.y - 1 - i as u32 / size.x);
+        tilemap.tiles[(y * size.x + x) as usize] = Some(TileData {
+            index: gid - tileset.first_gid,
+            solid,
+            flip_x: raw_gid & FLIP_X != 0,
+            flip_y: raw_gid & FLIP_Y != 0,
+            flip_d: raw_gid & FLIP_D != 0,
+        });
+    }
+
+    let mut tilemaps = tilemaps.into_iter().collect::<Vec<(usize, TileMapData)>>();
+    tilemaps.sort_by_key(|(i, _)| *i);
+    Ok(tilemaps.into_iter().map(|(_, t)| t).collect())
+}
+
+fn parse_object(
+    object: Node,
+    to_world: impl Fn(Vec2) -> Vec2,
+    level: &mut Level,
+    spawn: &mut Option<Vec2>,
+) -> Result<(), Error> {
+    let class = object
+        .attribute("class")
+        .or(object.attribute("type"))
+        .unwrap_or_default();
+    let name = object
+        .attribute("name")
+        .filter(|n| !n.is_empty())
+        .unwrap_or(class)
+        .to_string();
+    let pos = Vec2::new(attr(object, "x")?, attr(object, "y")?);
+    let size = Vec2::new(
+        attr_or(object, "width", 0f32)?,
+        attr_or(object, "height", 0f32)?,
+    );
+    // Tiled rotates clockwise in degrees around the top left corner with y pointing down
+    let rotation = -attr_or(object, "rotation", 0f32)?.to_radians();
+    let shape = object
+        .children()
+        .find(|n| n.is_element() && !n.has_tag_name("properties"));
+
+    if object.attribute("gid").is_some() {
+        warn!("Tile object {name} is not supported and will be ignored");
+        return Ok(());
+    }
+
+    // The center of rectangles and ellipses, and the position of points
+    let center = match shape.map(|s| s.tag_name().name()) {
+        Some("point") => to_world(pos),
+        _ => to_world(pos) + Vec2::from_angle(rotation).rotate(size * Vec2::new(0.5f32, -0.5f32)),
+    };
+
+    match class {
+        "PlayerSpawn" => {
+            *spawn = Some(center);
+            return Ok(());
+        }
+        "GrapplePoint" => {
+            level.grapple_points.push(GrapplePointData {
+                name,
+                position: center,
+            });
+            return Ok(());
+        }
+        "Room" => {
+            level.rooms.push(parse_room(
+                name,
+                shape,
+                to_world(pos),
+                center,
+                size,
+                rotation,
+            )?);
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    match shape.map(|s| (s.tag_name().name(), s)) {
+        None => level.solids.push(Solid {
+            name,
+            position: center,
+            size,
+            collider_size: None,
+            rotation,
+            color: None,
+            hidden: false,
+        }),
+        Some(("polygon", s)) | Some(("polyline", s)) => level.polygons.push(PolygonSolid {
+            name,
+            position: to_world(pos),
+            rotation,
+            vertices: parse_points(attr::<String>(s, "points")?.as_str())?,
+            closed: s.has_tag_name("polygon"),
+        }),
+        Some(("ellipse", _)) => level.polygons.push(PolygonSolid {
+            name,
+            position: center,
+            rotation,
+            vertices: (0..ELLIPSE_SEGMENTS)
+                .map(|i| Vec2::from_angle(i as f32 / ELLIPSE_SEGMENTS as f32 * TAU) * size / 2f32)
+                .collect(),
+            closed: true,
+        }),
+        Some((shape, _)) => warn!("Object {name} is a {shape}, which will be ignored"),
+    }
+    Ok(())
+}
+
+fn parse_room(
+    name: String,
+    shape: Option<Node>,
+    position: Vec2,
+    center: Vec2,
+    size: Vec2,
+    rotation: f32,
+) -> Result<Room, Error> {
+    let vertices = match shape {
+        None => [
+            Vec2::new(-0.5f32, -0.5f32),
+            Vec2::new(0.5f32, -0.5f32),
+            Vec2::new(0.5f32, 0.5f32),
+            Vec2::new(-0.5f32, 0.5f32),
+        ]
+        .into_iter()
+        .map(|v| center + Vec2::from_angle(rotation).rotate(v * size))
+        .collect::<Vec<Vec2>>(),
+        Some(s) if s.has_tag_name("polygon") => {
+            parse_points(attr::<String>(s, "points")?.as_str())?
+                .into_iter()
+                .map(|v| position + Vec2::from_angle(rotation).rotate(v))
+                .collect()
+        }
+        Some(s) => {
+            return Err(Error::msg(format!(
+                "Room {name} is a {}, rooms have to be rectangles or polygons",
+                s.tag_name().name()
+            )))
+        }
+    };
+
+    let min = vertices.iter().fold(Vec2::MAX, |a, b| a.min(*b));
+    let max = vertices.iter().fold(Vec2::MIN, |a, b| a.max(*b));
+    Ok(Room {
+        name,
+        min,
+        max,
+        // Unrotated rectangles don't need the polygon
+        polygon: match shape.is_none() && rotation == 0f32 {
+            true => None,
+            false => Some(vertices),
+        },
+    })
+}
+
+/// Parses Tiled polygon points (`"0,0 10,0 10,10"`), flipping them so y points up
+fn parse_points(points: &str) -> Result<Vec<Vec2>, Error> {
+    points
+        .split_whitespace()
+        .map(|p| {
+            let (x, y) = match p.split_once(',') {
+                Some(p) => p,
+                None => return Err(Error::msg(format!("Invalid polygon point {p}"))),
+            };
+            Ok(Vec2::new(x.parse()?, -y.parse::<f32>()?))
+        })
+        .collect()
+}
+
+fn property<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|n| n.has_tag_name("properties"))?
+        .children()
+        .find(|n| n.has_tag_name("property") && n.attribute("name") == Some(name))?
+        .attribute("value")
+}
+
+fn attr<T>(node: Node, name: &str) -> Result<T, Error>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match node.attribute(name) {
+        Some(v) => Ok(v.parse::<T>()?),
+        None => Err(Error::msg(format!(
+            "<{}> is missing the attribute {name}",
+            node.tag_name().name()
+        ))),
+    }
+}
+
+fn attr_or<T>(node: Node, name: &str, default: T) -> Result<T, Error>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match node.attribute(name) {
+        Some(_) => attr(node, name),
+        None => Ok(default),
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use bevy::{math::URect, prelude::*};
+use bevy_ecs_tilemap::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+pub const TILES_SHEET: &str = "sprites/tiles_sheet.png";
+pub const TILES_SHEET_TILE_SIZE: f32 = 70f32;
+
+/// Tilemaps are drawn behind everything else, each one slightly in front of the previous
+pub const TILEMAP_Z: f32 = -1f32;
+pub const TILEMAP_Z_STEP: f32 = 0.01f32;
+
+/// A grid based level section rendered with bevy_ecs_tilemap
+///
+/// Texture indices go left to right, top to bottom through the texture, so for tiles_sheet (12
+/// columns) `grassMid` is 114 and `grassCenter` is 8
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(from = "TextTileMap")]
+pub struct TileMapData {
+    pub name: String,
+    /// Path to the texture relative to the assets folder
+    pub texture: String,
+    /// Size of one tile in the texture, in pixels
+    pub texture_tile_size: Vec2,
+    /// Space between the tiles in the texture, in pixels
+    pub texture_spacing: Vec2,
+    /// Size of one tile in the world
+    pub tile_size: Vec2,
+    /// World position of the bottom left corner of the map
+    pub origin: Vec2,
+    pub size: UVec2,
+    /// Row major, bottom row first
+    pub tiles: Vec<Option<TileData>>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TileData {
+    pub index: u32,
+    #[serde(default = "default_solid")]
+    pub solid: bool,
+    #[serde(default)]
+    pub flip_x: bool,
+    #[serde(default)]
+    pub flip_y: bool,
+    /// Flips along the diagonal, which together with the other flips gives rotations
+    #[serde(default)]
+    pub flip_d: bool,
+}
+
+/// How tilemaps are written in level files
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct TextTileMap {
+    #[serde(default = "default_name")]
+    name: String,
+    #[serde(default = "default_texture")]
+    texture: String,
+    #[serde(default = "default_texture_tile_size")]
+    texture_tile_size: Vec2,
+    #[serde(default)]
+    texture_spacing: Vec2,
+    /// Tiles are square in level files
+    tile_size: f32,
+    origin: Vec2,
+    /// What each character in `rows` means. Spaces are always empty
+    legend: HashMap<char, TileData>,
+    /// The tiles, top row first
+    rows: Vec<String>,
+}
+
+fn default_name() -> String {
+    "Tilemap".to_string()
+}
+
+fn default_texture() -> String {
+    TILES_SHEET.to_string()
+}
+
+fn default_texture_tile_size() -> Vec2 {
+    Vec2::splat(TILES_SHEET_TILE_SIZE)
+}
+
+fn default_solid() -> bool {
+    true
+}
+
+impl From<TextTileMap> for TileMapData {
+    fn from(value: TextTileMap) -> Self {
+        let size = UVec2::new(
+            value
+                .rows
+                .iter()
+                .map(|r| r.chars().count())
+                .max()
+                .unwrap_or(0) as u32,
+            value.rows.len() as u32,
+        );
+        let mut tiles = vec![None; (size.x * size.y) as usize];
+        for (row, line) in value.rows.iter().rev().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if c == ' ' {
+                    continue;
+                }
+                match value.legend.get(&c) {
+                    Some(t) => tiles[row * size.x as usize + x] = Some(*t),
+                    None => warn!("Tilemap \"{}\" has no legend entry for '{c}'", value.name),
+                }
+            }
+        }
+
+        Self {
+            name: value.name,
+            texture: value.texture,
+            texture_tile_size: value.texture_tile_size,
+            texture_spacing: value.texture_spacing,
+            tile_size: Vec2::splat(value.tile_size),
+            origin: value.origin,
+            size,
+            tiles,
+        }
+    }
+}
+
+impl TileMapData {
+    /// Every tile with its position, (0, 0) is the bottom left tile
+    pub fn tiles(&self) -> impl Iterator<Item = (UVec2, TileData)> + '_ {
+        self.tiles.iter().enumerate().filter_map(|(i, t)| {
+            t.map(|t| {
+                let i = i as u32;
+                (UVec2::new(i % self.size.x, i / self.size.x), t)
+            })
+        })
+    }
+
+    /// Solid tiles merged into as few rectangles as possible, so the character controller doesn't
+    /// catch on the seams between tiles
+    pub fn solid_rects(&self) -> Vec<URect> {
+        let solid = self
+            .tiles
+            .iter()
+            .map(|t| t.is_some_and(|t| t.solid))
+            .collect::<Vec<bool>>();
+        merge_rects(&solid, self.size)
+    }
+}
+
+/// Greedily merges a grid of cells into rectangles, first along x and then along y
+pub fn merge_rects(cells: &[bool], size: UVec2) -> Vec<URect> {
+    let idx = |x: u32, y: u32| (y * size.x + x) as usize;
+    let mut used = vec![false; cells.len()];
+    let mut rects = Vec::new();
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            if !cells[idx(x, y)] || used[idx(x, y)] {
+                continue;
+            }
+
+            let mut width = 1;
+            while x + width < size.x && cells[idx(x + width, y)] && !used[idx(x + width, y)] {
+                width += 1;
+            }
+
+            let mut height = 1;
+            while y + height < size.y
+                && (x..x + width).all(|x| cells[idx(x, y + height)] && !used[idx(x, y + height)])
+            {
+                height += 1;
+            }
+
+            for y in y..y + height {
+                for x in x..x + width {
+                    used[idx(x, y)] = true;
+                }
+            }
+            rects.push(URect::new(x, y, x + width, y + height));
+        }
+    }
+
+    rects
+}
+
+pub(super) fn spawn_tilemap(
+    cmd: &mut Commands,
+    asset_server: &AssetServer,
+    root: Entity,
+    data: &TileMapData,
+    z: f32,
+) {
+    let map_size = TilemapSize {
+        x: data.size.x,
+        y: data.size.y,
+    };
+    let tile_size = TilemapTileSize {
+        x: data.texture_tile_size.x,
+        y: data.texture_tile_size.y,
+    };
+    let grid_size: TilemapGridSize = tile_size.into();
+
+    let tilemap = cmd.spawn_empty().set_parent(root).id();
+    let mut storage = TileStorage::empty(map_size);
+    for (pos, tile) in data.tiles() {
+        let tile_pos = TilePos { x: pos.x, y: pos.y };
+        let tile_entity = cmd
+            .spawn(TileBundle {
+                position: tile_pos,
+                tilemap_id: TilemapId(tilemap),
+                texture_index: TileTextureIndex(tile.index),
+                flip: TileFlip {
+                    x: tile.flip_x,
+                    y: tile.flip_y,
+                    d: tile.flip_d,
+                },
+                ..Default::default()
+            })
+            .set_parent(tilemap)
+            .id();
+        storage.set(&tile_pos, tile_entity);
+    }
+
+    // The tilemap is drawn in texture pixels and scaled down to the world tile size
+    let scale = data.tile_size / data.texture_tile_size;
+    let first_tile = data.origin + data.tile_size / 2f32;
+    cmd.entity(tilemap).insert((
+        TilemapBundle {
+            grid_size,
+            map_type: TilemapType::default(),
+            size: map_size,
+            storage,
+            texture: TilemapTexture::Single(asset_server.load(data.texture.as_str())),
+            tile_size,
+            spacing: TilemapSpacing {
+                x: data.texture_spacing.x,
+                y: data.texture_spacing.y,
+            },
+            transform: Transform::from_translation(first_tile.extend(z))
+                .with_scale(scale.extend(1f32)),
+            ..Default::default()
+        },
+        Name::from(data.name.as_str()),
+    ));
+
+    for rect in data.solid_rects() {
+        let min = data.origin + rect.min.as_vec2() * data.tile_size;
+        let half_size = rect.size().as_vec2() * data.tile_size / 2f32;
+        cmd.spawn((
+            TransformBundle::from_transform(Transform::from_translation(
+                (min + half_size).extend(0f32),
+            )),
+            Collider::cuboid(half_size.x, half_size.y),
+            Name::from(format!("{} Collider", data.name)),
+        ))
+        .set_parent(root);
+    }
+}
@@ -0,0 +1,30 @@
+// Bevy systems take their data as parameters, so they have long argument lists and query types
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+use bevy::{app::PluginGroupBuilder, prelude::*};
+
+#[cfg(feature = "headless")]
+pub mod headless;
+pub mod level;
+pub mod physics;
+pub mod sprites;
+pub mod player;
+
+pub const DEBUG: bool = true;
+
+pub const fn debug() -> bool {
+    DEBUG
+}
+
+/// The plugins of the game itself, without bevy or the third party plugins it uses
+pub struct GamePlugins;
+
+impl PluginGroup for GamePlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(sprites::SpritesPlugin)
+            .add(level::LevelPlugin)
+            .add(physics::PhysicsPlugin)
+            .add(player::PlayerPlugin)
+    }
+}
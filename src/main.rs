@@ -1,45 +1,24 @@
+use std::time::Duration;
+
 use bevy::{
     app::PluginGroupBuilder,
+    asset::ChangeWatcher,
     prelude::*,
     window::{PresentMode, WindowMode},
 };
-use bevy_editor_pls::EditorPlugin;
-use bevy_rapier2d::prelude::*;
-use seldom_state::StateMachinePlugin;
-
-pub mod level;
-pub mod sprites;
-pub mod player;
-
-pub const DEBUG: bool = true;
-
-pub const fn debug() -> bool {
-    DEBUG
-}
-
-struct GamePlugins;
-
-impl PluginGroup for GamePlugins {
-    fn build(self) -> PluginGroupBuilder {
-        PluginGroupBuilder::start::<Self>()
-            .add(sprites::SpritesPlugin)
-            .add(level::LevelPlugin)
-            .add(player::PlayerPlugin)
-    }
-}
+use bevy_ecs_tilemap::TilemapPlugin;
+use platformer::{GamePlugins, DEBUG};
 
 struct OtherPlugins;
 
 impl PluginGroup for OtherPlugins {
     fn build(self) -> PluginGroupBuilder {
-        let builder = PluginGroupBuilder::start::<Self>()
-            .add(StateMachinePlugin::default())
-            .add(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100f32));
+        let builder = PluginGroupBuilder::start::<Self>().add(TilemapPlugin);
+        #[cfg(feature = "editor")]
         if DEBUG {
-            builder.add(EditorPlugin::default())
-        } else {
-            builder
+            return builder.add(bevy_editor_pls::EditorPlugin::default());
         }
+        builder
     }
 }
 
@@ -57,7 +36,15 @@ fn main() {
                     }),
                     ..Default::default()
                 })
-                .set(ImagePlugin::default_nearest()),
+                .set(ImagePlugin::default_nearest())
+                .set(AssetPlugin {
+                    // Hot reloading for sprite sheets and levels
+                    watch_for_changes: match DEBUG {
+                        true => ChangeWatcher::with_delay(Duration::from_millis(200)),
+                        false => None,
+                    },
+                    ..Default::default()
+                }),
             GamePlugins,
             OtherPlugins,
         ))
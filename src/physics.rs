@@ -0,0 +1,153 @@
+use bevy::{prelude::*, transform::TransformSystem};
+use bevy_rapier2d::prelude::*;
+
+/// Length of a fixed update in seconds, movement and physics run at this rate no matter the
+/// frame rate
+pub const FIXED_TIMESTEP: f32 = 1f32 / 60f32;
+
+/// Steps rapier in [`FixedUpdate`] instead of once per frame, and interpolates the transforms of
+/// [`Interpolated`] entities between the last two steps so movement is smooth at any frame rate
+pub(super) struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(
+            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100f32)
+                .with_default_system_setup(false),
+        )
+        .insert_resource(FixedTime::new_from_secs(FIXED_TIMESTEP))
+        .insert_resource(RapierConfiguration {
+            timestep_mode: TimestepMode::Fixed {
+                dt: FIXED_TIMESTEP,
+                substeps: 1,
+            },
+            ..Default::default()
+        })
+        .configure_sets(
+            FixedUpdate,
+            (
+                PhysicsSet::SyncBackend,
+                PhysicsSet::SyncBackendFlush,
+                PhysicsSet::StepSimulation,
+                PhysicsSet::Writeback,
+            )
+                .chain(),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackend)
+                    .in_set(PhysicsSet::SyncBackend),
+                RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackendFlush)
+                    .in_set(PhysicsSet::SyncBackendFlush),
+                RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::StepSimulation)
+                    .in_set(PhysicsSet::StepSimulation),
+                RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::Writeback)
+                    .in_set(PhysicsSet::Writeback),
+            ),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                restore_physics_transform
+                    .in_set(InterpolationSet::Restore)
+                    .before(PhysicsSet::SyncBackend),
+                record_physics_transform
+                    .in_set(InterpolationSet::Record)
+                    .after(PhysicsSet::Writeback),
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            interpolate_transform
+                .in_set(InterpolationSet::Interpolate)
+                .before(TransformSystem::TransformPropagate),
+        )
+        .register_type::<Interpolated>();
+    }
+}
+
+/// Where the physics transforms of [`Interpolated`] entities are restored and recorded in
+/// [`FixedUpdate`], movement runs between the two. They are interpolated in [`PostUpdate`], before
+/// transforms are propagated
+#[derive(SystemSet, Clone, Copy, PartialEq, Debug, Hash, Eq)]
+pub enum InterpolationSet {
+    Restore,
+    Record,
+    Interpolate,
+}
+
+/// Renders the entity between its positions of the last two fixed updates. Only the translation
+/// is interpolated, and the entity shouldn't have a parent
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+    /// The translation that was rendered, if it changes outside of a fixed update the entity was
+    /// moved on purpose (like respawning) and snaps there
+    rendered: Option<Vec3>,
+    /// False until the first fixed update
+    stepped: bool,
+}
+
+impl Interpolated {
+    /// The translation after the last fixed update
+    pub fn current(&self) -> Vec3 {
+        self.current
+    }
+}
+
+/// Puts the entity back where the physics left it before the next fixed update
+fn restore_physics_transform(
+    mut query: Query<(&mut Transform, &mut GlobalTransform, &mut Interpolated)>,
+) {
+    for (mut transform, mut global, mut interpolated) in query.iter_mut() {
+        match interpolated.rendered {
+            Some(r) if r == transform.translation => transform.translation = interpolated.current,
+            _ => {
+                interpolated.previous = transform.translation;
+                interpolated.current = transform.translation;
+            }
+        }
+        interpolated.rendered = None;
+        // Movement reads the global transform before rapier propagates it
+        *global = GlobalTransform::from(*transform);
+    }
+}
+
+fn record_physics_transform(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.previous = match interpolated.stepped {
+            true => interpolated.current,
+            false => transform.translation,
+        };
+        interpolated.current = transform.translation;
+        interpolated.stepped = true;
+    }
+}
+
+fn interpolate_transform(
+    mut query: Query<(&mut Transform, &mut GlobalTransform, &mut Interpolated)>,
+    fixed_time: Res<FixedTime>,
+) {
+    let alpha = (fixed_time.accumulated().as_secs_f32() / fixed_time.period.as_secs_f32())
+        .clamp(0f32, 1f32);
+    for (mut transform, mut global, mut interpolated) in query.iter_mut() {
+        if !interpolated.stepped {
+            continue;
+        }
+        if interpolated
+            .rendered
+            .is_some_and(|r| r != transform.translation)
+        {
+            interpolated.previous = transform.translation;
+            interpolated.current = transform.translation;
+            interpolated.rendered = None;
+        } else {
+            transform.translation = interpolated.previous.lerp(interpolated.current, alpha);
+            interpolated.rendered = Some(transform.translation);
+        }
+        // The camera follows the global transform before it is propagated
+        *global = GlobalTransform::from(*transform);
+    }
+}
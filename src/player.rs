@@ -1,10 +1,13 @@
 use bevy::{app::PluginGroupBuilder, prelude::*};
 
+pub mod animation;
 pub mod camera;
+pub mod facing;
 pub mod input;
 pub mod visuals;
 pub mod state_machine;
 pub mod movement;
+pub mod replay;
 
 struct PlayerPlugins;
 
@@ -14,8 +17,12 @@ impl PluginGroup for PlayerPlugins {
             .add(input::InputPlugin)
             .add(camera::CameraPlugin)
             .add(visuals::VisualsPlugin)
+            .add(animation::AnimationPlugin)
+            .add(facing::FacingPlugin)
             .add(state_machine::StateMachinePlugin)
             .add(movement::MovementPlugin)
+            .add(movement::profile::MovementProfilePlugin)
+            .add(replay::ReplayPlugin)
     }
 }
 
@@ -28,6 +35,24 @@ impl Plugin for PlayerPlugin {
     }
 }
 
+/// Only the parts of the player that move it, without input devices, the camera or sprites. The
+/// player still needs an `ActionState<InputAction>` and a `TextureAtlasSprite` (for its size)
+#[cfg(feature = "headless")]
+pub(crate) struct HeadlessPlayerPlugin;
+
+#[cfg(feature = "headless")]
+impl Plugin for HeadlessPlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ConfigurePlayerSetsPlugin,
+            state_machine::StateMachinePlugin,
+            movement::MovementPlugin,
+        ))
+        .add_event::<camera::shake::CameraShake>()
+        .add_systems(Startup, init.in_set(PlayerSet::Main));
+    }
+}
+
 #[derive(Component)]
 pub struct Player;
 
@@ -69,6 +94,18 @@ impl Plugin for ConfigurePlayerSetsPlugin {
             )
                 .chain(),
         )
+        // Input is latched, the state machine transitions and then the player moves, once every
+        // fixed update
+        .configure_sets(
+            FixedUpdate,
+            (
+                PlayerSet::Input,
+                PlayerSet::StateMachine,
+                PlayerSet::Movement,
+                PlayerSet::PostPlayer,
+            )
+                .chain(),
+        )
         .configure_sets(
             Startup,
             (
@@ -0,0 +1,243 @@
+use crate::sprites::NamedSprite;
+
+use super::{input::InputAction, state_machine::states::*, Player, PlayerSet};
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+pub(super) struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, init.in_set(PlayerSet::Visuals))
+            .add_systems(
+                Update,
+                (choose_clip, animate).chain().in_set(PlayerSet::Visuals),
+            )
+            .register_type::<PlayerAnimation>()
+            .register_type::<AnimationClip>();
+    }
+}
+
+fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>) {
+    cmd.entity(player_query.single())
+        .insert(PlayerAnimation::new(AnimationClip::Idle));
+}
+
+/// Going from the first clip to the second plays the third clip in between
+const TRANSITIONS: &[(AnimationClip, AnimationClip, AnimationClip)] = &[
+    (
+        AnimationClip::Fall,
+        AnimationClip::Idle,
+        AnimationClip::Land,
+    ),
+    (
+        AnimationClip::Fall,
+        AnimationClip::Walk,
+        AnimationClip::Land,
+    ),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum AnimationClip {
+    Idle,
+    Walk,
+    Jump,
+    Fall,
+    WallSlide,
+    Duck,
+    Land,
+    Hurt,
+}
+
+pub struct ClipData {
+    /// Sprite names in player_sheet
+    pub frames: &'static [&'static str],
+    pub fps: f32,
+    pub looping: bool,
+    /// If false the clip plays to the end before the next clip starts
+    pub interruptible: bool,
+}
+
+impl AnimationClip {
+    pub fn data(self) -> ClipData {
+        match self {
+            Self::Idle => ClipData {
+                frames: &["p1_stand"],
+                fps: 1f32,
+                looping: true,
+                interruptible: true,
+            },
+            Self::Walk => ClipData {
+                frames: &[
+                    "p1_walk01",
+                    "p1_walk02",
+                    "p1_walk03",
+                    "p1_walk04",
+                    "p1_walk05",
+                    "p1_walk06",
+                    "p1_walk07",
+                    "p1_walk08",
+                    "p1_walk09",
+                    "p1_walk10",
+                    "p1_walk11",
+                ],
+                fps: 20f32,
+                looping: true,
+                interruptible: true,
+            },
+            // The sheet only has one frame for being in the air
+            Self::Jump | Self::Fall => ClipData {
+                frames: &["p1_jump"],
+                fps: 1f32,
+                looping: false,
+                interruptible: true,
+            },
+            Self::WallSlide => ClipData {
+                frames: &["p1_front"],
+                fps: 1f32,
+                looping: true,
+                interruptible: true,
+            },
+            Self::Duck => ClipData {
+                frames: &["p1_duck"],
+                fps: 1f32,
+                looping: true,
+                interruptible: true,
+            },
+            Self::Land => ClipData {
+                frames: &["p1_duck"],
+                fps: 12f32,
+                looping: false,
+                interruptible: true,
+            },
+            Self::Hurt => ClipData {
+                frames: &["p1_hurt"],
+                fps: 2f32,
+                looping: false,
+                interruptible: false,
+            },
+        }
+    }
+}
+
+/// Plays the clip for the current state of the player, use [`PlayerAnimation::play_once`] for
+/// clips that aren't tied to a state (like [`AnimationClip::Hurt`])
+#[derive(Component, Clone, Debug, PartialEq, Reflect)]
+pub struct PlayerAnimation {
+    clip: AnimationClip,
+    /// Starts when `clip` finishes
+    next: Option<AnimationClip>,
+    elapsed: f32,
+}
+
+impl PlayerAnimation {
+    pub fn new(clip: AnimationClip) -> Self {
+        Self {
+            clip,
+            next: None,
+            elapsed: 0f32,
+        }
+    }
+
+    pub fn clip(&self) -> AnimationClip {
+        self.clip
+    }
+
+    /// Plays `clip` and then goes back to the clip for the current state
+    pub fn play_once(&mut self, clip: AnimationClip) {
+        let next = self.next.unwrap_or(self.clip);
+        self.start(clip);
+        self.next = Some(next);
+    }
+
+    /// Looping clips never finish
+    pub fn finished(&self) -> bool {
+        let data = self.clip.data();
+        !data.looping && self.elapsed * data.fps >= data.frames.len() as f32
+    }
+
+    /// Switches to `clip`, going through a transition clip if there is one
+    fn request(&mut self, clip: AnimationClip) {
+        let data = self.clip.data();
+        if !self.finished() && (!data.interruptible || self.next == Some(clip)) {
+            self.next = Some(clip);
+            return;
+        }
+        if clip == self.clip {
+            return;
+        }
+
+        match TRANSITIONS
+            .iter()
+            .find(|(from, to, _)| *from == self.clip && *to == clip)
+        {
+            Some((_, _, via)) => {
+                self.start(*via);
+                self.next = Some(clip);
+            }
+            None => self.start(clip),
+        }
+    }
+
+    fn start(&mut self, clip: AnimationClip) {
+        self.clip = clip;
+        self.next = None;
+        self.elapsed = 0f32;
+    }
+
+    fn tick(&mut self, delta: f32) {
+        self.elapsed += delta;
+        if self.finished() {
+            if let Some(next) = self.next.take() {
+                self.start(next);
+            }
+        }
+    }
+
+    /// The name of the sprite to show
+    pub fn frame(&self) -> &'static str {
+        let data = self.clip.data();
+        let len = data.frames.len();
+        let frame = (self.elapsed * data.fps) as usize;
+        match data.looping {
+            true => data.frames[frame % len],
+            false => data.frames[frame.min(len - 1)],
+        }
+    }
+}
+
+fn choose_clip(
+    mut query: Query<(
+        &mut PlayerAnimation,
+        Option<&GroundedState>,
+        Option<&InAirState>,
+        Option<&WallState>,
+        Option<&ActionState<InputAction>>,
+    )>,
+) {
+    for (mut animation, grounded, in_air, wall, input) in query.iter_mut() {
+        let ducking = input.is_some_and(|i| i.pressed(InputAction::Down));
+        let clip = match (grounded, in_air, wall) {
+            (Some(GroundedState::Idle), _, _) if ducking => AnimationClip::Duck,
+            (Some(GroundedState::Idle), _, _) => AnimationClip::Idle,
+            (Some(GroundedState::Walking), _, _) => AnimationClip::Walk,
+            (Some(GroundedState::Jumping), _, _) => AnimationClip::Jump,
+            (_, Some(InAirState::Rising | InAirState::Grapple), _) => AnimationClip::Jump,
+            (_, Some(InAirState::Falling), _) => AnimationClip::Fall,
+            (_, _, Some(WallState::Sliding)) => AnimationClip::WallSlide,
+            (_, _, Some(WallState::Rising | WallState::Jumping)) => AnimationClip::Jump,
+            _ => continue,
+        };
+        animation.request(clip);
+    }
+}
+
+fn animate(mut query: Query<(&mut PlayerAnimation, &mut NamedSprite)>, time: Res<Time>) {
+    for (mut animation, mut sprite) in query.iter_mut() {
+        animation.tick(time.delta_seconds());
+        let frame = animation.frame();
+        if sprite.name != frame {
+            sprite.name = frame.to_string();
+        }
+    }
+}
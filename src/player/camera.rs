@@ -1,16 +1,39 @@
 use std::collections::HashMap;
 
-use bevy::prelude::*;
-// TODO: Implement lookahead and smoothing
+use bevy::{prelude::*, transform::TransformSystem};
 
-use super::{Player, PlayerSet};
+use crate::{level::Room, physics::InterpolationSet};
+
+pub mod framing;
+pub mod shake;
+pub mod zoom;
+
+use framing::*;
+use shake::*;
+use zoom::*;
+
+use super::{
+    movement::velocity::KinematicVelocity, state_machine::states::GroundedState, Player, PlayerSet,
+};
 
 pub(super) struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, init.in_set(PlayerSet::Camera))
-            .add_systems(Update, follow_camera.in_set(PlayerSet::Camera));
+            .add_event::<CameraShake>()
+            // Follows where the player is rendered, after its transform is interpolated
+            .add_systems(
+                PostUpdate,
+                (frame_targets, zoom_camera, follow_camera, shake_camera)
+                    .chain()
+                    .after(InterpolationSet::Interpolate)
+                    .before(TransformSystem::TransformPropagate),
+            )
+            .register_type::<CameraShaker>()
+            .register_type::<CameraFocusPoints>()
+            .register_type::<CameraTargets>()
+            .register_type::<PointOfInterest>();
     }
 }
 
@@ -21,38 +44,277 @@ fn init(mut cmd: Commands, query: Query<Entity, With<Player>>) {
         },
         FollowCamera {
             entity: query.single(),
-            lookahead: None,
-            smoothing: None,
+            lookahead: Some(CameraOptions {
+                amount: 0.3f32,
+                multi: Vec2::new(1f32, 0.5f32),
+            }),
+            smoothing: Some(CameraOptions {
+                amount: 0.15f32,
+                multi: Vec2::new(1f32, 1.5f32),
+            }),
+            rise_multi: 2f32,
+            room_transition_time: 0.5f32,
+            dead_zone: Some(Vec2::new(20f32, 0f32)),
+            platform_snapping: Some(75f32),
+            zoom: Some(ZoomOptions {
+                base: 1f32,
+                max: 1.75f32,
+                speed_multi: 0.0006f32,
+                smoothing: 0.4f32,
+                margin: 100f32,
+            }),
+            zoom_vel: 0f32,
+            focus: None,
+            smoothing_vel: Vec2::ZERO,
+            room: None,
+            transition: None,
+            position: None,
+            framed: Vec::new(),
         },
+        CameraShaker::new(1.5f32, Vec2::splat(20f32), 0.05f32, 15f32, 0),
         Name::from("Camera"),
     ));
 }
 
-#[derive(Component, Copy, Clone, Debug)]
+#[derive(Component, Clone, Debug)]
 pub struct FollowCamera {
     pub entity: Entity,
     pub lookahead: Option<CameraOptions>,
     pub smoothing: Option<CameraOptions>,
+    /// Multiplies the vertical smoothing while the target is above the camera, so the camera
+    /// follows falls more closely than jumps
+    pub rise_multi: f32,
+    /// How long it takes to pan over when the target goes into another [`Room`]
+    pub room_transition_time: f32,
+    /// Half the size of a rectangle around the followed point that the target can move in without
+    /// moving the camera
+    pub dead_zone: Option<Vec2>,
+    /// Only follow the target vertically when it lands, or when it gets further away than this
+    pub platform_snapping: Option<f32>,
+    pub zoom: Option<ZoomOptions>,
+    zoom_vel: f32,
+    /// The point that the camera follows, before lookahead and rooms
+    focus: Option<Vec2>,
+    smoothing_vel: Vec2,
+    /// The room the camera is kept inside of
+    room: Option<Entity>,
+    /// Where the camera was when the room changed, and how long ago that was
+    transition: Option<(Vec2, f32)>,
+    /// Where the camera is without screen shake
+    position: Option<Vec2>,
+    /// Everything besides `entity` that is kept in view, with its weight
+    framed: Vec<(Vec2, f32)>,
+}
+
+impl FollowCamera {
+    pub fn room(&self) -> Option<Entity> {
+        self.room
+    }
 }
 
 fn follow_camera(
-    mut cam_query: Query<(&mut Transform, &FollowCamera)>,
-    entity_query: Query<(Entity, &GlobalTransform), Without<FollowCamera>>,
+    mut cam_query: Query<(&mut Transform, &mut FollowCamera, &OrthographicProjection)>,
+    entity_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            Option<&KinematicVelocity>,
+            Option<&GroundedState>,
+        ),
+        Without<FollowCamera>,
+    >,
+    room_query: Query<(Entity, &Room)>,
+    time: Res<Time>,
 ) {
     let entity_map = entity_query
         .iter()
-        .collect::<HashMap<Entity, &GlobalTransform>>();
+        .map(|(e, t, v, g)| {
+            let target = Target {
+                pos: t.translation().truncate(),
+                vel: v.map(|v| Vec2::from(*v)).unwrap_or_default(),
+                grounded: g.is_some(),
+            };
+            (e, target)
+        })
+        .collect::<HashMap<Entity, Target>>();
 
-    for (mut transform, cam) in cam_query.iter_mut() {
-        let pos = match entity_map.get(&cam.entity) {
-            Some(pos) => pos,
+    for (mut transform, mut cam, projection) in cam_query.iter_mut() {
+        let target_data = match entity_map.get(&cam.entity) {
+            Some(t) => *t,
             None => continue,
         };
 
-        transform.translation = pos.translation();
+        let focus = follow_focus(&cam, target_data);
+        cam.focus = Some(focus);
+        let lookahead = match cam.lookahead {
+            Some(options) => lookahead_offset(options, target_data.vel),
+            None => Vec2::ZERO,
+        };
+        let mut target = weighted_center(focus, &cam.framed) + lookahead;
+
+        let current = cam.position.unwrap_or(transform.translation.truncate());
+        let room = current_room(&cam, target_data.pos, &room_query);
+        if room != cam.room {
+            if cam.room.is_some() {
+                cam.transition = Some((current, 0f32));
+            }
+            cam.room = room;
+        }
+        if let Some((_, room)) = room.and_then(|r| room_query.get(r).ok()) {
+            target = confine(room, target, projection.area.size() / 2f32);
+        }
+
+        let dt = time.delta_seconds();
+        let new_pos = match (cam.transition, cam.smoothing) {
+            (Some((from, elapsed)), _) => {
+                let elapsed = elapsed + dt;
+                let t = (elapsed / cam.room_transition_time).min(1f32);
+                cam.transition = match t < 1f32 {
+                    true => Some((from, elapsed)),
+                    false => None,
+                };
+                cam.smoothing_vel = Vec2::ZERO;
+                // Smoothstep, so the pan eases in and out
+                from.lerp(target, t * t * (3f32 - 2f32 * t))
+            }
+            (None, Some(options)) => {
+                let mut smooth_time = options.amount * options.multi;
+                if target.y > current.y {
+                    smooth_time.y *= cam.rise_multi;
+                }
+
+                let vel = &mut cam.smoothing_vel;
+                Vec2::new(
+                    smooth_damp(current.x, target.x, &mut vel.x, smooth_time.x, dt),
+                    smooth_damp(current.y, target.y, &mut vel.y, smooth_time.y, dt),
+                )
+            }
+            (None, None) => target,
+        };
+        cam.position = Some(new_pos);
+        transform.translation = new_pos.extend(transform.translation.z);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Target {
+    pos: Vec2,
+    vel: Vec2,
+    grounded: bool,
+}
+
+/// Moves the focus just enough to keep the target in the dead zone, and vertically only when the
+/// target is grounded or too far away if platform snapping is on
+fn follow_focus(cam: &FollowCamera, target: Target) -> Vec2 {
+    let focus = match cam.focus {
+        Some(f) => f,
+        None => return target.pos,
+    };
+    let dead_zone = cam.dead_zone.unwrap_or(Vec2::ZERO);
+    let follow_axis = |focus: f32, target: f32, half_size: f32| {
+        focus.clamp(target - half_size, target + half_size)
+    };
+
+    let y = match cam.platform_snapping {
+        Some(_) if target.grounded => target.pos.y,
+        Some(threshold) => follow_axis(focus.y, target.pos.y, threshold),
+        None => follow_axis(focus.y, target.pos.y, dead_zone.y),
+    };
+    Vec2::new(follow_axis(focus.x, target.pos.x, dead_zone.x), y)
+}
+
+/// The room the target is in, if it isn't in any room the camera stays in the last one
+fn current_room(
+    cam: &FollowCamera,
+    target: Vec2,
+    room_query: &Query<(Entity, &Room)>,
+) -> Option<Entity> {
+    // Prefer the current room where rooms overlap
+    if let Some(Ok((entity, room))) = cam.room.map(|r| room_query.get(r)) {
+        if room.contains(target) {
+            return Some(entity);
+        }
     }
+
+    room_query
+        .iter()
+        .find(|(_, r)| r.contains(target))
+        .map(|(e, _)| e)
+        .or(cam.room.filter(|r| room_query.contains(*r)))
+}
+
+/// How many times the view is pushed back inside a polygon room, each push can move another
+/// corner out of it
+const POLYGON_CONFINE_ITERATIONS: usize = 4;
+
+/// Moves the camera so a view of `half_size` stays inside the room, it is centered on axes where
+/// the room is smaller than the view
+fn confine(room: &Room, pos: Vec2, half_size: Vec2) -> Vec2 {
+    let mut pos = confine_rect(room.rect(), room.closest_point(pos), half_size);
+    if room.polygon.is_none() {
+        return pos;
+    }
+
+    // Push the view back by however far its corners stick out of the polygon
+    for _ in 0..POLYGON_CONFINE_ITERATIONS {
+        let (mut min_push, mut max_push) = (Vec2::ZERO, Vec2::ZERO);
+        for corner in [
+            pos - half_size,
+            pos + Vec2::new(half_size.x, -half_size.y),
+            pos + half_size,
+            pos + Vec2::new(-half_size.x, half_size.y),
+        ] {
+            let push = room.closest_point(corner) - corner;
+            min_push = min_push.min(push);
+            max_push = max_push.max(push);
+        }
+        let push = min_push + max_push;
+        if push.length_squared() < f32::EPSILON {
+            break;
+        }
+        pos = confine_rect(room.rect(), pos + push, half_size);
+    }
+    pos
+}
+
+fn confine_rect(rect: Rect, pos: Vec2, half_size: Vec2) -> Vec2 {
+    let confine_axis =
+        |pos: f32, min: f32, max: f32, half_size: f32| match max - min < half_size * 2f32 {
+            true => (min + max) / 2f32,
+            false => pos.clamp(min + half_size, max - half_size),
+        };
+
+    Vec2::new(
+        confine_axis(pos.x, rect.min.x, rect.max.x, half_size.x),
+        confine_axis(pos.y, rect.min.y, rect.max.y, half_size.y),
+    )
+}
+
+/// Critically damped spring towards `target`, which gets there in roughly `smooth_time` seconds
+/// and stays stable with any `dt`
+fn smooth_damp(current: f32, target: f32, vel: &mut f32, smooth_time: f32, dt: f32) -> f32 {
+    if smooth_time <= 0f32 {
+        *vel = 0f32;
+        return target;
+    }
+
+    let omega = 2f32 / smooth_time;
+    let x = omega * dt;
+    // Approximation of e^-x
+    let exp = 1f32 / (1f32 + x + 0.48f32 * x * x + 0.235f32 * x * x * x);
+    let change = current - target;
+    let temp = (*vel + omega * change) * dt;
+    *vel = (*vel - omega * temp) * exp;
+    target + (change + temp) * exp
+}
+
+/// How far ahead of the target the camera looks when it moves with `vel`
+fn lookahead_offset(options: CameraOptions, vel: Vec2) -> Vec2 {
+    vel * options.multi * options.amount
 }
 
+/// For lookahead `amount` is how many seconds ahead of the target the camera looks, for smoothing
+/// it is how many seconds it takes to catch up with the target. `multi` scales it per axis
 #[derive(Copy, Clone, Debug)]
 pub struct CameraOptions {
     pub amount: f32,
@@ -0,0 +1,85 @@
+use super::*;
+use crate::player::movement::{grappler::Grappler, slingshot::Slingshot};
+
+/// How much the camera is pulled towards the point the target is grappling or can slingshot to,
+/// [`FollowCamera::entity`] has a weight of 1
+const GRAPPLE_POINT_WEIGHT: f32 = 0.5f32;
+const SLINGSHOT_POINT_WEIGHT: f32 = 0.25f32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct CameraTarget {
+    pub entity: Entity,
+    /// Compared to [`FollowCamera::entity`], which has a weight of 1
+    pub weight: f32,
+}
+
+/// More entities on the camera entity to keep in view along with [`FollowCamera::entity`], like
+/// other players
+#[derive(Component, Clone, Debug, Default, PartialEq, Reflect)]
+pub struct CameraTargets(pub Vec<CameraTarget>);
+
+/// Framed by cameras while their target is within `radius` of it
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct PointOfInterest {
+    pub weight: f32,
+    pub radius: f32,
+}
+
+/// Finds the position and weight of everything the camera frames besides its target
+pub(super) fn frame_targets(
+    mut cam_query: Query<(&mut FollowCamera, Option<&CameraTargets>)>,
+    target_query: Query<(
+        Option<&Grappler>,
+        Option<&Slingshot>,
+        Option<&GroundedState>,
+    )>,
+    transform_query: Query<&GlobalTransform>,
+    poi_query: Query<(&GlobalTransform, &PointOfInterest)>,
+) {
+    for (mut cam, targets) in cam_query.iter_mut() {
+        let mut framed = Vec::new();
+        let mut frame = |entity: Entity, weight: f32| {
+            if let Ok(transform) = transform_query.get(entity) {
+                framed.push((transform.translation().truncate(), weight));
+            }
+        };
+
+        for target in targets.iter().flat_map(|t| t.0.iter()) {
+            frame(target.entity, target.weight);
+        }
+        // Only pulled towards grapple points in the air, so the camera stays put on the ground
+        if let Ok((grappler, slingshot, None)) = target_query.get(cam.entity) {
+            if let Some(point) = grappler.and_then(|g| g.get_current()) {
+                frame(point, GRAPPLE_POINT_WEIGHT);
+            }
+            if let Some(point) = slingshot.and_then(|s| s.get_closest()) {
+                frame(point, SLINGSHOT_POINT_WEIGHT);
+            }
+        }
+
+        if let Ok(target) = transform_query.get(cam.entity) {
+            let target = target.translation().truncate();
+            for (transform, poi) in poi_query.iter() {
+                let pos = transform.translation().truncate();
+                if pos.distance_squared(target) <= poi.radius * poi.radius {
+                    framed.push((pos, poi.weight));
+                }
+            }
+        }
+
+        cam.framed = framed;
+    }
+}
+
+/// The weighted average of the followed point (with a weight of 1) and the framed targets
+pub(super) fn weighted_center(focus: Vec2, framed: &[(Vec2, f32)]) -> Vec2 {
+    let (sum, weight) = framed
+        .iter()
+        .fold((focus, 1f32), |(sum, weight), (pos, w)| {
+            (sum + *pos * *w, weight + *w)
+        });
+    match weight > 0f32 {
+        true => sum / weight,
+        false => focus,
+    }
+}
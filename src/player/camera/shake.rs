@@ -0,0 +1,105 @@
+use super::*;
+
+/// Adds trauma to every [`CameraShaker`], from 0 to 1. The shake grows with the square of the
+/// trauma, so small amounts barely move the camera
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct CameraShake {
+    pub trauma: f32,
+}
+
+/// Trauma based screen shake, which is added on top of where [`FollowCamera`] puts the camera.
+/// The shake is noise seeded by `seed`, so the same trauma over the same frames always shakes
+/// the same way
+#[derive(Component, Clone, Debug, PartialEq, Reflect)]
+pub struct CameraShaker {
+    /// Trauma lost per second
+    pub decay: f32,
+    pub max_offset: Vec2,
+    /// In radians
+    pub max_rotation: f32,
+    /// How fast the shake changes direction
+    pub frequency: f32,
+    pub seed: u32,
+    trauma: f32,
+    /// Time since the trauma went above 0
+    time: f32,
+}
+
+impl CameraShaker {
+    pub fn new(decay: f32, max_offset: Vec2, max_rotation: f32, frequency: f32, seed: u32) -> Self {
+        Self {
+            decay,
+            max_offset,
+            max_rotation,
+            frequency,
+            seed,
+            trauma: 0f32,
+            time: 0f32,
+        }
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0f32, 1f32);
+    }
+
+    /// The offset and rotation the camera has right now
+    pub fn shake(&self) -> (Vec2, f32) {
+        let shake = self.trauma * self.trauma;
+        let t = self.time * self.frequency;
+        let offset = Vec2::new(noise(self.seed, t), noise(self.seed.wrapping_add(1), t));
+        let rotation = noise(self.seed.wrapping_add(2), t);
+        (
+            offset * self.max_offset * shake,
+            rotation * self.max_rotation * shake,
+        )
+    }
+}
+
+pub(super) fn shake_camera(
+    mut query: Query<(&mut CameraShaker, &mut Transform)>,
+    mut events: EventReader<CameraShake>,
+    time: Res<Time>,
+) {
+    let trauma = events.iter().map(|e| e.trauma).sum::<f32>();
+
+    for (mut shaker, mut transform) in query.iter_mut() {
+        if shaker.trauma <= 0f32 {
+            shaker.time = 0f32;
+        }
+        shaker.add_trauma(trauma);
+        if shaker.trauma <= 0f32 && transform.rotation == Quat::IDENTITY {
+            continue;
+        }
+
+        let (offset, rotation) = shaker.shake();
+        transform.translation += offset.extend(0f32);
+        transform.rotation = Quat::from_rotation_z(rotation);
+
+        let decay = shaker.decay * time.delta_seconds();
+        shaker.add_trauma(-decay);
+        shaker.time += time.delta_seconds();
+    }
+}
+
+/// Smooth noise from -1 to 1 that only depends on `seed` and `t`
+fn noise(seed: u32, t: f32) -> f32 {
+    let i = t.floor();
+    let f = t - i;
+    let a = hash(seed, i as i32);
+    let b = hash(seed, i as i32 + 1);
+    a + (b - a) * f * f * (3f32 - 2f32 * f)
+}
+
+fn hash(seed: u32, i: i32) -> f32 {
+    let mut x = seed ^ (i as u32).wrapping_mul(0x9e3779b9);
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846ca68b);
+    x ^= x >> 16;
+    x as f32 / u32::MAX as f32 * 2f32 - 1f32
+}
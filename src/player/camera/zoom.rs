@@ -0,0 +1,96 @@
+use super::*;
+use crate::{
+    level::GrapplePoint,
+    player::movement::{grappler::Grappler, slingshot::Slingshot},
+};
+
+/// Zooms out (by raising the orthographic scale) with the speed of the target, and to keep the
+/// focus points in view
+#[derive(Copy, Clone, Debug, PartialEq, Reflect)]
+pub struct ZoomOptions {
+    /// The scale when standing still
+    pub base: f32,
+    pub max: f32,
+    /// Scale added per unit of speed
+    pub speed_multi: f32,
+    /// Roughly how many seconds it takes to reach the desired zoom
+    pub smoothing: f32,
+    /// Space kept between focus points and the edge of the screen
+    pub margin: f32,
+}
+
+/// Points on the camera entity that it zooms out to keep in view
+#[derive(Component, Clone, Debug, Default, PartialEq, Reflect)]
+pub struct CameraFocusPoints(pub Vec<Vec2>);
+
+pub(super) fn zoom_camera(
+    mut cam_query: Query<(
+        &mut FollowCamera,
+        &mut OrthographicProjection,
+        &Transform,
+        Option<&CameraFocusPoints>,
+    )>,
+    target_query: Query<
+        (
+            &GlobalTransform,
+            Option<&KinematicVelocity>,
+            Option<&GroundedState>,
+            Option<&Grappler>,
+            Option<&Slingshot>,
+        ),
+        Without<FollowCamera>,
+    >,
+    point_query: Query<&GlobalTransform, With<GrapplePoint>>,
+    time: Res<Time>,
+) {
+    for (mut cam, mut projection, transform, focus_points) in cam_query.iter_mut() {
+        let options = match cam.zoom {
+            Some(o) => o,
+            None => continue,
+        };
+        let (target, vel, grounded, grappler, slingshot) = match target_query.get(cam.entity) {
+            Ok(t) => t,
+            Err(_) => continue,
+        };
+
+        let mut points = focus_points.map(|p| p.0.clone()).unwrap_or_default();
+        points.extend(cam.framed.iter().map(|(pos, _)| *pos));
+        if !points.is_empty() {
+            points.push(target.translation().truncate());
+        }
+        let mut desired = options.base;
+        // Back to normal once the target lands
+        if grounded.is_none() {
+            let speed = vel.map(|v| Vec2::from(*v).length()).unwrap_or(0f32);
+            desired += speed * options.speed_multi;
+
+            // Keep the anchor of the swing or launch in view
+            let anchor = grappler
+                .and_then(|g| g.get_current())
+                .or(slingshot.and_then(|s| s.launched_from()));
+            if let Some(point) = anchor.and_then(|a| point_query.get(a).ok()) {
+                points.push(point.translation().truncate());
+            }
+        }
+
+        let center = cam.position.unwrap_or(transform.translation.truncate());
+        // The size of half the screen at a scale of 1
+        let half_screen = projection.area.size() / 2f32 / projection.scale;
+        // The area isn't known until the camera has been updated once
+        if half_screen.min_element() <= 0f32 {
+            points.clear();
+        }
+        for point in points.iter() {
+            let needed = ((*point - center).abs() + options.margin) / half_screen;
+            desired = desired.max(needed.max_element());
+        }
+        desired = desired.clamp(options.base, options.max);
+
+        let dt = time.delta_seconds();
+        let current = projection.scale;
+        let new = smooth_damp(current, desired, &mut cam.zoom_vel, options.smoothing, dt);
+        if new != current {
+            projection.scale = new;
+        }
+    }
+}
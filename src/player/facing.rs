@@ -0,0 +1,102 @@
+use super::{
+    input::InputAction,
+    movement::{velocity::KinematicVelocity, wall_movement::WallMovement},
+    state_machine::states::WallState,
+    Player, PlayerSet,
+};
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+/// Below this the input and velocity don't change which way the player is facing
+const INPUT_THRESHOLD: f32 = 0.1f32;
+const VELOCITY_THRESHOLD: f32 = 5f32;
+
+pub(super) struct FacingPlugin;
+
+impl Plugin for FacingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, init.in_set(PlayerSet::Visuals))
+            .add_systems(
+                Update,
+                (update_facing, flip_sprites)
+                    .chain()
+                    .in_set(PlayerSet::Visuals),
+            )
+            .register_type::<Facing>();
+    }
+}
+
+fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>) {
+    cmd.entity(player_query.single()).insert(Facing::Right);
+}
+
+/// Which way an entity is looking, sprites are drawn facing right
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum Facing {
+    Left,
+    #[default]
+    Right,
+}
+
+impl Facing {
+    /// `None` if `x` is zero
+    pub fn from_x(x: f32) -> Option<Self> {
+        match x {
+            x if x < 0f32 => Some(Self::Left),
+            x if x > 0f32 => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    /// -1 for left and 1 for right
+    pub fn sign(self) -> f32 {
+        match self {
+            Self::Left => -1f32,
+            Self::Right => 1f32,
+        }
+    }
+
+    pub fn to_vec2(self) -> Vec2 {
+        Vec2::X * self.sign()
+    }
+}
+
+/// Faces away from the wall while sliding, otherwise towards the input and then the velocity
+fn update_facing(
+    mut query: Query<(
+        &mut Facing,
+        Option<&KinematicVelocity>,
+        Option<&ActionState<InputAction>>,
+        Option<&WallMovement>,
+        Option<&WallState>,
+    )>,
+) {
+    for (mut facing, vel, input, wall_mover, wall_state) in query.iter_mut() {
+        let wall = match (wall_state, wall_mover.and_then(|w| w.get_current_wall())) {
+            (Some(WallState::Sliding), Some((_, side))) => Facing::from_x(-side as f32),
+            _ => None,
+        };
+        let input = input
+            .map(|i| i.clamped_value(InputAction::Run))
+            .filter(|x| x.abs() > INPUT_THRESHOLD)
+            .and_then(Facing::from_x);
+        let vel = vel
+            .map(|v| v.x)
+            .filter(|x| x.abs() > VELOCITY_THRESHOLD)
+            .and_then(Facing::from_x);
+
+        let new = match wall.or(input).or(vel) {
+            Some(f) => f,
+            None => continue,
+        };
+        if *facing != new {
+            *facing = new;
+        }
+    }
+}
+
+fn flip_sprites(mut query: Query<(&Facing, &mut TextureAtlasSprite), Changed<Facing>>) {
+    for (facing, mut sprite) in query.iter_mut() {
+        sprite.flip_x = *facing == Facing::Left;
+    }
+}
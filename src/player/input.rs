@@ -1,32 +1,59 @@
 use super::{Player, PlayerSet};
 use bevy::{prelude::*, reflect::TypePath};
-use leafwing_input_manager::{axislike::VirtualAxis, prelude::*};
+use leafwing_input_manager::{axislike::VirtualAxis, prelude::*, user_input::InputKind};
+use serde::{Deserialize, Serialize};
+
+pub mod controls;
+pub mod gamepad;
+pub mod latch;
+
+use controls::*;
+use gamepad::*;
+use latch::*;
 
 pub(super) struct InputPlugin;
 
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(InputManagerPlugin::<InputAction>::default())
-            .add_systems(Startup, init.in_set(PlayerSet::Input));
+            .init_resource::<Rebinding>()
+            .add_event::<RebindAction>()
+            .add_systems(PreStartup, load_controls)
+            .add_systems(Startup, init.in_set(PlayerSet::Input))
+            .add_systems(
+                Update,
+                (
+                    rebind_controls,
+                    rebind,
+                    apply_controls,
+                    assign_gamepads,
+                    latch_input,
+                )
+                    .chain()
+                    .in_set(PlayerSet::Input),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_latched_input.in_set(PlayerSet::Input),
+                    settle_input.in_set(PlayerSet::PostPlayer),
+                ),
+            );
     }
 }
 
-fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>) {
+fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>, controls: Res<Controls>) {
     cmd.entity(player_query.single())
         .insert(InputManagerBundle {
             action_state: ActionState::default(),
-            input_map: InputMap::default()
-                .insert(VirtualAxis::horizontal_arrow_keys(), InputAction::Run)
-                .insert(KeyCode::C, InputAction::Jump)
-                //.insert(KeyCode::X, InputAction::Grapple)
-                .insert(KeyCode::X, InputAction::Slingshot)
-                .insert(KeyCode::Down, InputAction::Down)
-                .insert(KeyCode::ShiftLeft, InputAction::Land)
-                .build(),
-        });
+            input_map: controls.input_map(),
+        })
+        .insert(LatchedInput::default());
 }
 
-#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, TypePath)]
+#[derive(
+    Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, TypePath, Serialize, Deserialize,
+)]
 pub enum InputAction {
     Run,
     Jump,
@@ -0,0 +1,348 @@
+use std::fs;
+
+use super::*;
+use serde::{Deserialize, Serialize};
+
+/// Where the key bindings are saved, next to the executable's working directory
+pub const CONTROLS_FILE: &str = "controls.ron";
+
+/// The key and gamepad bindings of the player, loaded from [`CONTROLS_FILE`] at startup and saved to it
+/// whenever they change
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Controls {
+    pub run_left: KeyCode,
+    pub run_right: KeyCode,
+    pub jump: Option<KeyCode>,
+    pub grapple: Option<KeyCode>,
+    pub slingshot: Option<KeyCode>,
+    pub down: Option<KeyCode>,
+    pub land: Option<KeyCode>,
+    pub gamepad: GamepadControls,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            run_left: KeyCode::Left,
+            run_right: KeyCode::Right,
+            jump: Some(KeyCode::C),
+            grapple: Some(KeyCode::Z),
+            slingshot: Some(KeyCode::X),
+            down: Some(KeyCode::Down),
+            land: Some(KeyCode::ShiftLeft),
+            gamepad: GamepadControls::default(),
+        }
+    }
+}
+
+impl Controls {
+    pub fn input_map(&self) -> InputMap<InputAction> {
+        let mut map = InputMap::default();
+        map.insert(
+            VirtualAxis {
+                negative: InputKind::Keyboard(self.run_left),
+                positive: InputKind::Keyboard(self.run_right),
+            },
+            InputAction::Run,
+        );
+        for (key, action) in [
+            (self.jump, InputAction::Jump),
+            (self.grapple, InputAction::Grapple),
+            (self.slingshot, InputAction::Slingshot),
+            (self.down, InputAction::Down),
+            (self.land, InputAction::Land),
+        ] {
+            if let Some(key) = key {
+                map.insert(key, action);
+            }
+        }
+        self.gamepad.insert_into(&mut map);
+        map
+    }
+
+    fn key(&self, slot: Slot) -> Option<KeyCode> {
+        match slot {
+            Slot::RunLeft => Some(self.run_left),
+            Slot::RunRight => Some(self.run_right),
+            Slot::Action(InputAction::Run) => None,
+            Slot::Action(InputAction::Jump) => self.jump,
+            Slot::Action(InputAction::Grapple) => self.grapple,
+            Slot::Action(InputAction::Slingshot) => self.slingshot,
+            Slot::Action(InputAction::Down) => self.down,
+            Slot::Action(InputAction::Land) => self.land,
+        }
+    }
+
+    /// Errors if `key` is `None` for one of the run keys, which have to be bound
+    fn set_key(&mut self, slot: Slot, key: Option<KeyCode>) -> Result<(), String> {
+        let binding = match slot {
+            Slot::RunLeft | Slot::RunRight => {
+                let key = key.ok_or_else(|| format!("{slot:?} has to be bound to a key"))?;
+                match slot {
+                    Slot::RunLeft => self.run_left = key,
+                    _ => self.run_right = key,
+                }
+                return Ok(());
+            }
+            Slot::Action(InputAction::Run) => return Err("Run is bound to two keys".to_string()),
+            Slot::Action(InputAction::Jump) => &mut self.jump,
+            Slot::Action(InputAction::Grapple) => &mut self.grapple,
+            Slot::Action(InputAction::Slingshot) => &mut self.slingshot,
+            Slot::Action(InputAction::Down) => &mut self.down,
+            Slot::Action(InputAction::Land) => &mut self.land,
+        };
+        *binding = key;
+        Ok(())
+    }
+
+    /// Binds `key` to `slot`, whatever else was bound to `key` gets the previous key of `slot`.
+    /// Nothing changes if that would leave one of the run keys unbound
+    pub fn bind_key(&mut self, slot: Slot, key: KeyCode) -> Result<(), String> {
+        let previous = self.key(slot);
+        let mut bound = self.clone();
+        for other in Slot::all().filter(|s| *s != slot && self.key(*s) == Some(key)) {
+            bound
+                .set_key(other, previous)
+                .map_err(|e| format!("{key:?} is used by {other:?}, and {e}"))?;
+        }
+        bound.set_key(slot, Some(key))?;
+        *self = bound;
+        Ok(())
+    }
+
+    /// Reads [`CONTROLS_FILE`], `None` if there isn't one
+    pub fn load() -> Result<Option<Self>, String> {
+        let text = match fs::read_to_string(CONTROLS_FILE) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Could not read {CONTROLS_FILE}: {e}")),
+        };
+        ron::from_str(&text)
+            .map(Some)
+            .map_err(|e| format!("Could not parse {CONTROLS_FILE}: {e}"))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|e| format!("Could not serialize controls: {e}"))?;
+        fs::write(CONTROLS_FILE, text).map_err(|e| format!("Could not write {CONTROLS_FILE}: {e}"))
+    }
+}
+
+/// Where a key or button is bound, `Run` has a slot for each direction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Slot {
+    RunLeft,
+    RunRight,
+    /// Any action besides `Run`
+    Action(InputAction),
+}
+
+impl Slot {
+    pub fn all() -> impl Iterator<Item = Self> {
+        [Self::RunLeft, Self::RunRight]
+            .into_iter()
+            .chain(InputAction::variants().filter(|a| *a != InputAction::Run).map(Self::Action))
+    }
+}
+
+/// Starts rebinding the controls, or skips to the next action while rebinding
+pub const REBIND_KEY: KeyCode = KeyCode::F1;
+
+/// Starts listening for the next key press to bind to an action
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct RebindAction(pub InputAction);
+
+/// The action currently being rebound, `Run` takes two presses, left and then right. Once an
+/// action is bound the next one is rebound, until the last one
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rebinding {
+    pub action: Option<InputAction>,
+    left: Option<KeyCode>,
+    left_button: Option<GamepadButtonType>,
+}
+
+impl Rebinding {
+    /// Moves on to the action after `action`, or stops after the last one
+    fn next(&mut self, action: InputAction) {
+        *self = Self::default();
+        match InputAction::get_at(action.index() + 1) {
+            Some(next) => {
+                self.action = Some(next);
+                info!("Press a key for {next:?}, {REBIND_KEY:?} skips it");
+            }
+            None => info!("Done rebinding"),
+        }
+    }
+}
+
+pub(super) fn load_controls(mut cmd: Commands) {
+    let controls = match Controls::load() {
+        Ok(Some(c)) => c,
+        Ok(None) => {
+            let controls = Controls::default();
+            if let Err(e) = controls.save() {
+                warn!("{e}");
+            }
+            controls
+        }
+        // Keep the broken file so the changes in it aren't lost
+        Err(e) => {
+            warn!("{e}, using the default controls");
+            Controls::default()
+        }
+    };
+    cmd.insert_resource(controls);
+}
+
+/// Applies the controls to the players and saves them when they change
+pub(super) fn apply_controls(
+    mut query: Query<&mut InputMap<InputAction>, With<Player>>,
+    controls: Res<Controls>,
+) {
+    if !controls.is_changed() {
+        return;
+    }
+    for mut map in query.iter_mut() {
+        let gamepad = map.gamepad();
+        *map = controls.input_map();
+        if let Some(gamepad) = gamepad {
+            map.set_gamepad(gamepad);
+        }
+    }
+    if !controls.is_added() {
+        if let Err(e) = controls.save() {
+            warn!("{e}");
+        }
+    }
+}
+
+/// [`REBIND_KEY`] goes through every action, rebinding them one after another
+pub(super) fn rebind_controls(
+    mut events: EventWriter<RebindAction>,
+    rebinding: Res<Rebinding>,
+    keys: Res<Input<KeyCode>>,
+) {
+    if !keys.just_pressed(REBIND_KEY) {
+        return;
+    }
+    let next = match rebinding.action {
+        Some(action) => InputAction::get_at(action.index() + 1),
+        None => InputAction::get_at(0),
+    };
+    match next {
+        Some(action) => events.send(RebindAction(action)),
+        None => info!("Done rebinding"),
+    }
+}
+
+/// Binds the next key or gamepad button that is pressed to the action being rebound, escape
+/// cancels. A key that is already bound to something else is swapped with the previous key of the
+/// action
+pub(super) fn rebind(
+    mut events: EventReader<RebindAction>,
+    mut rebinding: ResMut<Rebinding>,
+    mut controls: ResMut<Controls>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+) {
+    if let Some(event) = events.iter().last() {
+        *rebinding = Rebinding {
+            action: Some(event.0),
+            ..Default::default()
+        };
+        info!("Press a key for {:?}, {REBIND_KEY:?} skips it", event.0);
+        // The key that started rebinding shouldn't be bound
+        return;
+    }
+
+    let action = match rebinding.action {
+        Some(a) => a,
+        None => return,
+    };
+    if keys.just_pressed(REBIND_KEY) {
+        // Skipping to the next action doesn't end rebinding
+        if InputAction::get_at(action.index() + 1).is_none() {
+            *rebinding = Rebinding::default();
+        }
+        return;
+    }
+    if let Some(button) = buttons.get_just_pressed().next() {
+        rebind_button(&mut rebinding, &mut controls, action, button.button_type);
+        return;
+    }
+    let key = match keys.get_just_pressed().next() {
+        Some(k) => *k,
+        None => return,
+    };
+    if key == KeyCode::Escape {
+        info!("Cancelled rebinding {action:?}");
+        *rebinding = Rebinding::default();
+        return;
+    }
+
+    let mut bound = controls.clone();
+    let result = match action {
+        InputAction::Run => {
+            let left = match rebinding.left {
+                Some(l) => l,
+                None => {
+                    rebinding.left = Some(key);
+                    info!("Press a key for {action:?} right");
+                    return;
+                }
+            };
+            if left == key {
+                Err(format!("{action:?} left and right can't both be {key:?}"))
+            } else {
+                bound
+                    .bind_key(Slot::RunLeft, left)
+                    .and_then(|_| bound.bind_key(Slot::RunRight, key))
+            }
+        }
+        _ => bound.bind_key(Slot::Action(action), key),
+    };
+    match result {
+        Ok(()) => {
+            info!("Bound {action:?} to {key:?}");
+            // Saved by `apply_controls`
+            *controls = bound;
+            rebinding.next(action);
+        }
+        Err(e) => {
+            warn!("Could not bind {action:?}: {e}");
+            rebinding.left = None;
+            info!("Press a key for {action:?}");
+        }
+    }
+}
+
+fn rebind_button(
+    rebinding: &mut Rebinding,
+    controls: &mut Controls,
+    action: InputAction,
+    button: GamepadButtonType,
+) {
+    if action == InputAction::Run {
+        let left = match rebinding.left_button {
+            Some(l) => l,
+            None => {
+                rebinding.left_button = Some(button);
+                info!("Press a button for {action:?} right");
+                return;
+            }
+        };
+        if left == button {
+            warn!("Could not bind {action:?}: left and right can't both be {button:?}");
+            rebinding.left_button = None;
+            return;
+        }
+        controls.gamepad.bind_button(Slot::RunLeft, left);
+        controls.gamepad.bind_button(Slot::RunRight, button);
+    } else {
+        controls.gamepad.bind_button(Slot::Action(action), button);
+    }
+    info!("Bound {action:?} to {button:?}");
+    rebinding.next(action);
+}
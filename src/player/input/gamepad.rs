@@ -0,0 +1,123 @@
+use super::*;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use serde::{Deserialize, Serialize};
+
+/// Gamepad bindings, part of [`Controls`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct GamepadControls {
+    /// Stick values closer to the center than this are ignored
+    pub deadzone: f32,
+    pub run_axis: Option<GamepadAxisType>,
+    pub run_left: Option<GamepadButtonType>,
+    pub run_right: Option<GamepadButtonType>,
+    pub jump: Option<GamepadButtonType>,
+    pub grapple: Option<GamepadButtonType>,
+    pub slingshot: Option<GamepadButtonType>,
+    pub down: Option<GamepadButtonType>,
+    pub land: Option<GamepadButtonType>,
+}
+
+impl Default for GamepadControls {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.2f32,
+            run_axis: Some(GamepadAxisType::LeftStickX),
+            run_left: Some(GamepadButtonType::DPadLeft),
+            run_right: Some(GamepadButtonType::DPadRight),
+            jump: Some(GamepadButtonType::South),
+            grapple: Some(GamepadButtonType::East),
+            slingshot: Some(GamepadButtonType::West),
+            down: Some(GamepadButtonType::DPadDown),
+            land: Some(GamepadButtonType::RightTrigger2),
+        }
+    }
+}
+
+impl GamepadControls {
+    pub fn insert_into(&self, map: &mut InputMap<InputAction>) {
+        if let Some(axis) = self.run_axis {
+            map.insert(SingleAxis::symmetric(axis, self.deadzone), InputAction::Run);
+        }
+        if let (Some(left), Some(right)) = (self.run_left, self.run_right) {
+            map.insert(
+                VirtualAxis {
+                    negative: InputKind::GamepadButton(left),
+                    positive: InputKind::GamepadButton(right),
+                },
+                InputAction::Run,
+            );
+        }
+        for (button, action) in [
+            (self.jump, InputAction::Jump),
+            (self.grapple, InputAction::Grapple),
+            (self.slingshot, InputAction::Slingshot),
+            (self.down, InputAction::Down),
+            (self.land, InputAction::Land),
+        ] {
+            if let Some(button) = button {
+                map.insert(button, action);
+            }
+        }
+    }
+
+    fn button_mut(&mut self, slot: Slot) -> Option<&mut Option<GamepadButtonType>> {
+        match slot {
+            Slot::RunLeft => Some(&mut self.run_left),
+            Slot::RunRight => Some(&mut self.run_right),
+            Slot::Action(InputAction::Run) => None,
+            Slot::Action(InputAction::Jump) => Some(&mut self.jump),
+            Slot::Action(InputAction::Grapple) => Some(&mut self.grapple),
+            Slot::Action(InputAction::Slingshot) => Some(&mut self.slingshot),
+            Slot::Action(InputAction::Down) => Some(&mut self.down),
+            Slot::Action(InputAction::Land) => Some(&mut self.land),
+        }
+    }
+
+    /// Binds `button` to `slot`, whatever else was bound to `button` gets the previous button of
+    /// `slot`
+    pub fn bind_button(&mut self, slot: Slot, button: GamepadButtonType) {
+        let previous = match self.button_mut(slot) {
+            Some(binding) => binding.replace(button),
+            None => return,
+        };
+        for other in Slot::all().filter(|s| *s != slot) {
+            if let Some(binding) = self.button_mut(other).filter(|b| **b == Some(button)) {
+                *binding = previous;
+            }
+        }
+    }
+}
+
+/// Gives players the first connected gamepad when theirs is disconnected, or when one is plugged
+/// in
+pub(super) fn assign_gamepads(
+    mut query: Query<&mut InputMap<InputAction>, With<Player>>,
+    mut events: EventReader<GamepadConnectionEvent>,
+    gamepads: Res<Gamepads>,
+) {
+    for event in events.iter() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                info!("Gamepad {} connected: {}", event.gamepad.id, info.name)
+            }
+            GamepadConnection::Disconnected => {
+                info!("Gamepad {} disconnected", event.gamepad.id)
+            }
+        }
+
+        for mut map in query.iter_mut() {
+            if map.gamepad().is_some_and(|g| gamepads.contains(g)) {
+                continue;
+            }
+            match gamepads.iter().next() {
+                Some(gamepad) => {
+                    map.set_gamepad(gamepad);
+                }
+                None => {
+                    map.clear_gamepad();
+                }
+            }
+        }
+    }
+}
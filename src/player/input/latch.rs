@@ -0,0 +1,78 @@
+use super::*;
+use leafwing_input_manager::buttonlike::ButtonState;
+
+/// Presses and releases that no fixed update has seen yet. A frame can run zero or several fixed
+/// updates, so each one gets at most one press or release per action and none are lost or
+/// doubled
+#[derive(Component, Default, Clone, Debug, PartialEq)]
+pub struct LatchedInput(Vec<(InputAction, ButtonState)>);
+
+impl LatchedInput {
+    fn push(&mut self, action: InputAction, state: ButtonState) {
+        let last = self.0.iter().rev().find(|(a, _)| *a == action);
+        if last.is_none_or(|(_, s)| *s != state) {
+            self.0.push((action, state));
+        }
+    }
+
+    fn pop(&mut self, action: InputAction) -> Option<ButtonState> {
+        let i = self.0.iter().position(|(a, _)| *a == action)?;
+        Some(self.0.remove(i).1)
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Keeps the presses and releases of `actions` for the next fixed update
+    pub fn latch(&mut self, actions: &ActionState<InputAction>) {
+        for action in InputAction::variants() {
+            let state = actions.action_data(action).state;
+            if just_changed(&state) {
+                self.push(action, state);
+            }
+        }
+    }
+}
+
+fn just_changed(state: &ButtonState) -> bool {
+    matches!(state, ButtonState::JustPressed | ButtonState::JustReleased)
+}
+
+/// Runs after the fixed updates of the frame, presses and releases that are left weren't seen by
+/// any of them
+pub(super) fn latch_input(mut query: Query<(&ActionState<InputAction>, &mut LatchedInput)>) {
+    for (actions, mut latched) in query.iter_mut() {
+        latched.latch(actions);
+    }
+}
+
+/// Replaces the input of the fixed update with the oldest press or release of each action
+pub(super) fn apply_latched_input(
+    mut query: Query<(&mut ActionState<InputAction>, &mut LatchedInput)>,
+) {
+    for (mut actions, mut latched) in query.iter_mut() {
+        for action in InputAction::variants() {
+            let data = actions.action_data_mut(action);
+            if just_changed(&data.state) {
+                latched.push(action, data.state);
+            }
+            if let Some(state) = latched.pop(action) {
+                data.state = state;
+            }
+        }
+    }
+}
+
+/// Presses and releases only last for one fixed update
+pub(super) fn settle_input(mut query: Query<&mut ActionState<InputAction>, With<LatchedInput>>) {
+    for mut actions in query.iter_mut() {
+        for action in InputAction::variants() {
+            let data = actions.action_data_mut(action);
+            data.state = match data.state {
+                ButtonState::JustPressed | ButtonState::Pressed => ButtonState::Pressed,
+                ButtonState::JustReleased | ButtonState::Released => ButtonState::Released,
+            };
+        }
+    }
+}
@@ -1,20 +1,28 @@
 use std::{collections::HashMap, time::Duration};
 
-use crate::level::GrapplePoint;
+use crate::{
+    level::GrapplePoint,
+    physics::{Interpolated, InterpolationSet},
+};
 
-use super::{input::InputAction, state_machine::states::*, Player, PlayerSet};
+use super::{
+    camera::shake::CameraShake, input::InputAction, state_machine::states::*, Player, PlayerSet,
+};
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
 
 pub mod grappler;
 pub mod gravity;
 pub mod horizontal_movement;
 pub mod jumper;
 pub mod lander;
+pub mod profile;
 pub mod slingshot;
 pub mod terminal_velocity;
+pub mod tuning;
 pub mod velocity;
 pub mod wall_movement;
 
@@ -23,8 +31,10 @@ use gravity::*;
 use horizontal_movement::*;
 use jumper::*;
 use lander::*;
+use profile::*;
 use slingshot::*;
 use terminal_velocity::*;
+use tuning::*;
 use velocity::*;
 use wall_movement::*;
 
@@ -32,21 +42,33 @@ pub(super) struct MovementPlugin;
 
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(KinematicGravity(Vec2::NEG_Y * 1000f32))
+        app.insert_resource(KinematicGravity(MovementProfile::default().gravity))
+            .configure_set(
+                FixedUpdate,
+                PlayerSet::Movement
+                    .after(InterpolationSet::Restore)
+                    .before(PhysicsSet::SyncBackend),
+            )
             .add_systems(Startup, init.in_set(PlayerSet::Visuals))
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
+                    kinematic_collisions,
+                    // Every force is added before the fall speed is capped
+                    (
+                        kinematic_gravity,
+                        horizontal_movement,
+                        wall_movement,
+                        jumper,
+                        grappler,
+                        slingshot,
+                        lander,
+                        terminal_velocity,
+                    )
+                        .chain(),
                     kinematic_velocity,
-                    kinematic_gravity,
-                    horizontal_movement,
-                    wall_movement,
-                    jumper,
-                    grappler,
-                    slingshot,
-                    terminal_velocity,
-                    lander,
                 )
+                    .chain()
                     .in_set(PlayerSet::Movement),
             )
             .register_type::<KinematicVelocity>()
@@ -57,12 +79,32 @@ impl Plugin for MovementPlugin {
             .register_type::<Slingshot>()
             .register_type::<Grappler>()
             .register_type::<WallMovement>()
-            .register_type::<Lander>();
+            .register_type::<Lander>()
+            // The fields of the movement components, for the state saved by the replay
+            .register_type::<Timer>()
+            .register_type::<TimerMode>()
+            .register_type::<bevy::time::Stopwatch>()
+            .register_type::<(Timer, i8)>()
+            .register_type::<(Entity, i8)>()
+            .register_type::<Option<(Entity, i8)>>()
+            .register_type::<Option<Entity>>()
+            .register_type::<(f32, f32)>()
+            .register_type::<Option<f32>>()
+            .register_type::<Option<InAirState>>();
     }
 }
 
+/// Spawns the player with the default [`MovementProfile`], until the selected preset has loaded
 fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>) {
-    cmd.entity(player_query.single()).insert((
+    let profile = MovementProfile::default();
+    let mut player = cmd.entity(player_query.single());
+    if let Some(grappler) = profile.grappler {
+        player.insert(grappler);
+    }
+    if let Some(lander) = profile.lander {
+        player.insert(lander);
+    }
+    player.insert((
         Collider::cuboid(12.5f32, 25f32),
         Friction {
             coefficient: 0f32,
@@ -73,19 +115,14 @@ fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>) {
             combine_rule: CoefficientCombineRule::Min,
         },
         RigidBody::KinematicPositionBased,
+        Interpolated::default(),
         KinematicVelocity::default(),
         KinematicGravityUser,
-        TerminalVelocity::new(-1150f32, 1.5f32, 0.1f32),
-        Jumper::new(400f32, 0.35f32, 1.25f32, 0.175f32, 0.2f32),
-        WallMovement::new(Vec2::new(450f32, 350f32), (-20f32, 20f32), 0.25f32, 0.2f32),
-        HorizontalMovement {
-            max_speed: 250f32,
-            acceleration_time: 0.2f32,
-            turn_around_multi: 1.5f32,
-            deccelration_time: 0.4f32,
-            air_control_multi: 0.7f32,
-            air_friction_multi: 0.2f32,
-        },
+        profile.terminal_velocity,
+        profile.jumper,
+        profile.wall_movement,
+        profile.horizontal,
+        profile.slingshot,
         KinematicCharacterController {
             offset: CharacterLength::Absolute(0.01f32),
             slide: true,
@@ -100,16 +137,5 @@ fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>) {
             apply_impulse_to_dynamic_bodies: true,
             ..Default::default()
         },
-        Slingshot::new(
-            750f32,
-            250f32,
-            Vec2::new(1.35f32, 1f32),
-            0.5f32,
-            0.5f32,
-            0.7f32,
-        ),
-        // these components are a definite maybe
-        //Lander::new(0.25f32, Vec2::new(15f32, 0.8f32), 1.2f32, 700f32, -50f32),
-        //Grappler::new(300f32, 75f32, 200f32, 0.008f32, 0.2f32, 0.4f32),
     ));
 }
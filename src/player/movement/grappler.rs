@@ -1,6 +1,8 @@
 use super::*;
 
-#[derive(Default, Component, Clone, Debug, PartialEq, Reflect)]
+#[derive(Component, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+#[serde(try_from = "GrapplerBuilder", into = "GrapplerBuilder")]
 pub struct Grappler {
     pub range: f32,
     pub min_desired: f32,
@@ -11,28 +13,19 @@ pub struct Grappler {
     current_point: Option<Entity>,
     closest_point: Option<Entity>,
     grapple_buffer_timer: Option<f32>,
+    /// Seconds grapple has been held for, counted in fixed steps
+    grapple_held: f32,
 }
 
 impl Grappler {
-    pub fn new(
-        range: f32,
-        min_desired: f32,
-        max_desired: f32,
-        far_springyness: f32,
-        close_springyness: f32,
-        grapple_buffer: f32,
-    ) -> Self {
-        Self {
-            range,
-            min_desired,
-            max_desired,
-            far_springyness,
-            close_springyness,
-            grapple_buffer,
-            current_point: None,
-            closest_point: None,
-            grapple_buffer_timer: None,
-        }
+    /// Takes the tuning values of `tuned`, and stays attached if it is grappling
+    pub fn retune(&mut self, tuned: &Self) {
+        self.range = tuned.range;
+        self.min_desired = tuned.min_desired;
+        self.max_desired = tuned.max_desired;
+        self.far_springyness = tuned.far_springyness;
+        self.close_springyness = tuned.close_springyness;
+        self.grapple_buffer = tuned.grapple_buffer;
     }
 
     pub fn can_grapple(&self) -> bool {
@@ -42,6 +35,112 @@ impl Grappler {
     pub fn is_grappling(&self) -> bool {
         self.current_point.is_some()
     }
+
+    pub fn get_current(&self) -> Option<Entity> {
+        self.current_point
+    }
+}
+
+tuned!(Grappler, GrapplerBuilder);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct GrapplerBuilder {
+    range: f32,
+    min_desired: f32,
+    max_desired: f32,
+    far_springyness: f32,
+    close_springyness: f32,
+    grapple_buffer: f32,
+}
+
+impl Default for GrapplerBuilder {
+    fn default() -> Self {
+        Self {
+            range: 300f32,
+            min_desired: 75f32,
+            max_desired: 200f32,
+            far_springyness: 0.008f32,
+            close_springyness: 0.2f32,
+            grapple_buffer: 0.4f32,
+        }
+    }
+}
+
+impl GrapplerBuilder {
+    /// How far away a grapple point can be
+    pub fn range(mut self, range: f32) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// The rope pushes the player away when closer than this
+    pub fn min_desired(mut self, min_desired: f32) -> Self {
+        self.min_desired = min_desired;
+        self
+    }
+
+    /// The rope pulls the player in when further than this
+    pub fn max_desired(mut self, max_desired: f32) -> Self {
+        self.max_desired = max_desired;
+        self
+    }
+
+    pub fn far_springyness(mut self, far_springyness: f32) -> Self {
+        self.far_springyness = far_springyness;
+        self
+    }
+
+    pub fn close_springyness(mut self, close_springyness: f32) -> Self {
+        self.close_springyness = close_springyness;
+        self
+    }
+
+    /// Seconds a grapple press is remembered for
+    pub fn grapple_buffer(mut self, grapple_buffer: f32) -> Self {
+        self.grapple_buffer = grapple_buffer;
+        self
+    }
+
+    pub fn build(self) -> Result<Grappler, TuningError> {
+        Checks::new("Grappler")
+            .positive("range", self.range)
+            .non_negative("min_desired", self.min_desired)
+            .ordered(
+                ("min_desired", self.min_desired),
+                ("max_desired", self.max_desired),
+            )
+            .ordered(("max_desired", self.max_desired), ("range", self.range))
+            .non_negative("far_springyness", self.far_springyness)
+            .non_negative("close_springyness", self.close_springyness)
+            .non_negative("grapple_buffer", self.grapple_buffer)
+            .finish()?;
+        Ok(Grappler {
+            range: self.range,
+            min_desired: self.min_desired,
+            max_desired: self.max_desired,
+            far_springyness: self.far_springyness,
+            close_springyness: self.close_springyness,
+            grapple_buffer: self.grapple_buffer,
+            current_point: None,
+            closest_point: None,
+            grapple_buffer_timer: None,
+            grapple_held: 0f32,
+        })
+    }
+}
+
+impl From<Grappler> for GrapplerBuilder {
+    fn from(grappler: Grappler) -> Self {
+        Self {
+            range: grappler.range,
+            min_desired: grappler.min_desired,
+            max_desired: grappler.max_desired,
+            far_springyness: grappler.far_springyness,
+            close_springyness: grappler.close_springyness,
+            grapple_buffer: grappler.grapple_buffer,
+        }
+    }
 }
 
 pub fn grappler(
@@ -51,17 +150,29 @@ pub fn grappler(
         &mut KinematicVelocity,
         &ActionState<InputAction>,
         &mut Jumper,
+        Option<&Slingshot>,
     )>,
     points: Query<(Entity, &GlobalTransform), With<GrapplePoint>>,
-    time: Res<Time>,
+    time: Res<FixedTime>,
 ) {
     let points_map = points.iter().collect::<HashMap<Entity, &GlobalTransform>>();
     let points_vec = points.iter().collect::<Vec<(Entity, &GlobalTransform)>>();
 
-    for (transform, mut grappler, mut vel, input, mut jumper) in grappler.iter_mut() {
+    for (transform, mut grappler, mut vel, input, mut jumper, slingshot) in grappler.iter_mut() {
         get_closest_points((transform, &mut grappler), &points_vec);
 
-        if grapple_buffer(&mut grappler, &input, &time) {
+        // Slingshotting lets go of the point, grapple has to be pressed again to grab another
+        if input.just_pressed(InputAction::Slingshot) && grappler.is_grappling() {
+            grappler.current_point = None;
+            grappler.grapple_buffer_timer = Some(grappler.grapple_buffer);
+        }
+        if grapple_buffer(&mut grappler, input, &time) {
+            continue;
+        }
+        // Releasing grapple this early slingshots instead, so it only grabs a point once it is
+        // held for longer, and the buffer starts from then
+        if slingshot.is_some_and(|s| grappler.grapple_held < s.grapple_tap_time) {
+            grappler.grapple_buffer_timer = Some(0f32);
             continue;
         }
 
@@ -75,7 +186,7 @@ pub fn grappler(
             Ok(v) => v,
             Err(s) => {
                 if let Some(s) = s {
-                    println!("{s}");
+                    debug!("{s}");
                 }
                 grappler.current_point = None;
                 continue;
@@ -124,14 +235,17 @@ fn get_closest_points(
 fn grapple_buffer(
     grappler: &mut Mut<Grappler>,
     input: &ActionState<InputAction>,
-    time: &Res<Time>,
+    time: &Res<FixedTime>,
 ) -> bool {
     if let Some(t) = grappler.grapple_buffer_timer {
-        grappler.grapple_buffer_timer = Some(t + time.delta_seconds());
+        grappler.grapple_buffer_timer = Some(t + time.period.as_secs_f32());
     }
 
     if input.just_pressed(InputAction::Grapple) {
         grappler.grapple_buffer_timer = Some(0f32);
+        grappler.grapple_held = 0f32;
+    } else if input.pressed(InputAction::Grapple) {
+        grappler.grapple_held += time.period.as_secs_f32();
     }
 
     if !input.pressed(InputAction::Grapple) {
@@ -175,8 +289,13 @@ fn get_point(
     }
 }
 
-fn grappler_forces(time: &Res<Time>, pos: Vec2, grappler: &mut Mut<Grappler>, point: Vec2) -> Vec2 {
-    let mut force = Vec2::splat(time.delta_seconds());
+fn grappler_forces(
+    time: &Res<FixedTime>,
+    pos: Vec2,
+    grappler: &mut Mut<Grappler>,
+    point: Vec2,
+) -> Vec2 {
+    let mut force = Vec2::splat(time.period.as_secs_f32());
 
     // Force if distance is less than the min desired distance
     let min_force = -grappler.close_springyness
@@ -201,5 +320,5 @@ fn grappler_forces(time: &Res<Time>, pos: Vec2, grappler: &mut Mut<Grappler>, po
         Vec2::ZERO
     };
 
-    return (point - pos) * force;
+    (point - pos) * force
 }
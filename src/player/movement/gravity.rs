@@ -11,7 +11,7 @@ pub struct KinematicGravityUser;
 pub fn kinematic_gravity(
     mut query: Query<(&mut KinematicVelocity, Option<&GravityScale>), With<KinematicGravityUser>>,
     gravity: Res<KinematicGravity>,
-    time: Res<Time>,
+    time: Res<FixedTime>,
 ) {
     for (mut vel, scale) in query.iter_mut() {
         let scale = match scale {
@@ -19,7 +19,7 @@ pub fn kinematic_gravity(
             None => 1f32,
         };
 
-        let add = gravity.0 * scale * time.delta_seconds();
+        let add = gravity.0 * scale * time.period.as_secs_f32();
         vel.x += add.x;
         vel.y += add.y;
     }
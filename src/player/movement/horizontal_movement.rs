@@ -1,6 +1,10 @@
 use super::*;
 
-#[derive(Default, Component, Clone, Debug, PartialEq, Reflect)]
+#[derive(Component, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(
+    try_from = "HorizontalMovementBuilder",
+    into = "HorizontalMovementBuilder"
+)]
 pub struct HorizontalMovement {
     pub max_speed: f32,
     pub acceleration_time: f32,
@@ -17,7 +21,7 @@ pub fn horizontal_movement(
         &KinematicCharacterControllerOutput,
         &ActionState<InputAction>,
     )>,
-    time: Res<Time>,
+    time: Res<FixedTime>,
 ) {
     for (mut vel, movement, output, input) in query.iter_mut() {
         let input_val = input.clamped_value(InputAction::Run);
@@ -36,13 +40,13 @@ fn deccelerate(
     vel: &mut Mut<KinematicVelocity>,
     movement: &HorizontalMovement,
     output: &KinematicCharacterControllerOutput,
-    time: &Res<Time>,
+    time: &Res<FixedTime>,
 ) {
     let sign = vel.x.signum();
     let air_multi = (!output.grounded as u8) as f32 * movement.air_friction_multi
         + (output.grounded as u8) as f32 * 1f32;
-    vel.x -=
-        movement.max_speed * sign * air_multi / movement.deccelration_time * time.delta_seconds();
+    vel.x -= movement.max_speed * sign * air_multi / movement.deccelration_time
+        * time.period.as_secs_f32();
     if vel.x.signum() != sign {
         vel.x = 0f32;
     }
@@ -53,14 +57,14 @@ fn accelerate(
     movement: &HorizontalMovement,
     output: &KinematicCharacterControllerOutput,
     input_val: f32,
-    time: &Res<Time>,
+    time: &Res<FixedTime>,
 ) {
     let mut aim_speed = movement.max_speed * input_val;
     if input_val.signum() != vel.x.signum() {
         aim_speed *= movement.turn_around_multi;
     }
 
-    let mut vel_add = aim_speed / movement.acceleration_time * time.delta_seconds();
+    let mut vel_add = aim_speed / movement.acceleration_time * time.period.as_secs_f32();
     if !output.grounded {
         vel_add *= movement.air_control_multi
     }
@@ -70,3 +74,95 @@ fn accelerate(
     }
     vel.x += vel_add;
 }
+
+tuned!(HorizontalMovement, HorizontalMovementBuilder);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct HorizontalMovementBuilder {
+    max_speed: f32,
+    acceleration_time: f32,
+    deccelration_time: f32,
+    turn_around_multi: f32,
+    air_control_multi: f32,
+    air_friction_multi: f32,
+}
+
+impl Default for HorizontalMovementBuilder {
+    fn default() -> Self {
+        Self {
+            max_speed: 250f32,
+            acceleration_time: 0.2f32,
+            deccelration_time: 0.4f32,
+            turn_around_multi: 1.5f32,
+            air_control_multi: 0.7f32,
+            air_friction_multi: 0.2f32,
+        }
+    }
+}
+
+impl HorizontalMovementBuilder {
+    pub fn max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+
+    /// Seconds to get from standing still to the max speed
+    pub fn acceleration_time(mut self, acceleration_time: f32) -> Self {
+        self.acceleration_time = acceleration_time;
+        self
+    }
+
+    /// Seconds to stop from the max speed
+    pub fn deccelration_time(mut self, deccelration_time: f32) -> Self {
+        self.deccelration_time = deccelration_time;
+        self
+    }
+
+    pub fn turn_around_multi(mut self, turn_around_multi: f32) -> Self {
+        self.turn_around_multi = turn_around_multi;
+        self
+    }
+
+    pub fn air_control_multi(mut self, air_control_multi: f32) -> Self {
+        self.air_control_multi = air_control_multi;
+        self
+    }
+
+    pub fn air_friction_multi(mut self, air_friction_multi: f32) -> Self {
+        self.air_friction_multi = air_friction_multi;
+        self
+    }
+
+    pub fn build(self) -> Result<HorizontalMovement, TuningError> {
+        Checks::new("HorizontalMovement")
+            .non_negative("max_speed", self.max_speed)
+            .positive("acceleration_time", self.acceleration_time)
+            .positive("deccelration_time", self.deccelration_time)
+            .non_negative("turn_around_multi", self.turn_around_multi)
+            .non_negative("air_control_multi", self.air_control_multi)
+            .non_negative("air_friction_multi", self.air_friction_multi)
+            .finish()?;
+        Ok(HorizontalMovement {
+            max_speed: self.max_speed,
+            acceleration_time: self.acceleration_time,
+            deccelration_time: self.deccelration_time,
+            turn_around_multi: self.turn_around_multi,
+            air_control_multi: self.air_control_multi,
+            air_friction_multi: self.air_friction_multi,
+        })
+    }
+}
+
+impl From<HorizontalMovement> for HorizontalMovementBuilder {
+    fn from(movement: HorizontalMovement) -> Self {
+        Self {
+            max_speed: movement.max_speed,
+            acceleration_time: movement.acceleration_time,
+            deccelration_time: movement.deccelration_time,
+            turn_around_multi: movement.turn_around_multi,
+            air_control_multi: movement.air_control_multi,
+            air_friction_multi: movement.air_friction_multi,
+        }
+    }
+}
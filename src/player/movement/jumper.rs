@@ -1,6 +1,10 @@
 use super::*;
 
-#[derive(Default, Component, Clone, Debug, PartialEq, Reflect)]
+const WALLJUMP_TRAUMA: f32 = 0.2f32;
+
+#[derive(Component, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+#[serde(try_from = "JumperBuilder", into = "JumperBuilder")]
 pub struct Jumper {
     pub jump_force: f32,
     pub x_multi: f32,
@@ -20,7 +24,8 @@ pub fn jumper(
         &ActionState<InputAction>,
         &KinematicCharacterControllerOutput,
     )>,
-    time: Res<Time>,
+    mut shake: EventWriter<CameraShake>,
+    time: Res<FixedTime>,
 ) {
     for (mut vel, mut jumper, mut wall_mover, grounded_state, wall_state, input, output) in
         query.iter_mut()
@@ -32,6 +37,7 @@ pub fn jumper(
             &mut vel,
             grounded_state,
             wall_state,
+            &mut shake,
         )
     }
 }
@@ -42,6 +48,7 @@ fn jumping(
     vel: &mut Mut<KinematicVelocity>,
     grounded_state: Option<&GroundedState>,
     wall_state: Option<&WallState>,
+    shake: &mut EventWriter<CameraShake>,
 ) {
     if grounded_state.is_some_and(|s| *s == GroundedState::Jumping) {
         let v = jumper.jump(Vec2::new(vel.x, vel.y));
@@ -53,7 +60,9 @@ fn jumping(
         let v = wall_mover.walljump(jumper, Vec2::new(vel.x, vel.y));
         vel.x = v.x;
         vel.y = v.y;
-        return;
+        shake.send(CameraShake {
+            trauma: WALLJUMP_TRAUMA,
+        });
     }
 }
 
@@ -63,43 +72,32 @@ fn jumper_timers(
     state: Option<&GroundedState>,
     input: &ActionState<InputAction>,
     output: &KinematicCharacterControllerOutput,
-    time: &Res<Time>,
+    time: &Res<FixedTime>,
 ) {
     if vel.y > 0f32 && input.released(InputAction::Jump) && jumper.can_release {
         jumper.can_release = false;
         vel.y *= jumper.release_multi;
     }
-    jumper
-        .coyote_time
-        .tick(Duration::from_secs_f32(time.delta_seconds()));
+    jumper.coyote_time.tick(time.period);
     if output.grounded && state.is_some_and(|s| *s != GroundedState::Jumping) {
         jumper.coyote_time.reset();
     }
 
-    jumper
-        .jump_buffer
-        .tick(Duration::from_secs_f32(time.delta_seconds()));
+    jumper.jump_buffer.tick(time.period);
     if input.just_pressed(InputAction::Jump) {
         jumper.jump_buffer.reset();
     }
 }
 
 impl Jumper {
-    pub fn new(
-        jump_force: f32,
-        release_multi: f32,
-        x_multi: f32,
-        coyote_time: f32,
-        jump_buffer_time: f32,
-    ) -> Self {
-        Self {
-            jump_force,
-            release_multi,
-            x_multi,
-            coyote_time: Timer::from_seconds(coyote_time, TimerMode::Once),
-            jump_buffer: Timer::from_seconds(jump_buffer_time, TimerMode::Once),
-            can_release: false,
-        }
+    /// Takes the tuning values of `tuned`, the timers keep running so a profile can be swapped
+    /// mid jump
+    pub fn retune(&mut self, tuned: &Self) {
+        self.jump_force = tuned.jump_force;
+        self.x_multi = tuned.x_multi;
+        self.release_multi = tuned.release_multi;
+        self.coyote_time.set_duration(tuned.coyote_time.duration());
+        self.jump_buffer.set_duration(tuned.jump_buffer.duration());
     }
 
     pub fn should_jump(&self) -> bool {
@@ -127,3 +125,87 @@ impl Jumper {
         current_vel + Vec2::new(0f32, self.jump_force) * Vec2::new(self.x_multi, 1f32)
     }
 }
+
+tuned!(Jumper, JumperBuilder);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct JumperBuilder {
+    jump_force: f32,
+    release_multi: f32,
+    x_multi: f32,
+    coyote_time: f32,
+    jump_buffer_time: f32,
+}
+
+impl Default for JumperBuilder {
+    fn default() -> Self {
+        Self {
+            jump_force: 400f32,
+            release_multi: 0.35f32,
+            x_multi: 1.25f32,
+            coyote_time: 0.175f32,
+            jump_buffer_time: 0.2f32,
+        }
+    }
+}
+
+impl JumperBuilder {
+    pub fn jump_force(mut self, jump_force: f32) -> Self {
+        self.jump_force = jump_force;
+        self
+    }
+
+    /// What the upwards velocity is multiplied by when jump is released early, from 0 to 1
+    pub fn release_multi(mut self, release_multi: f32) -> Self {
+        self.release_multi = release_multi;
+        self
+    }
+
+    pub fn x_multi(mut self, x_multi: f32) -> Self {
+        self.x_multi = x_multi;
+        self
+    }
+
+    /// Seconds after leaving the ground that jumping still works
+    pub fn coyote_time(mut self, coyote_time: f32) -> Self {
+        self.coyote_time = coyote_time;
+        self
+    }
+
+    /// Seconds before landing that a jump press is remembered
+    pub fn jump_buffer_time(mut self, jump_buffer_time: f32) -> Self {
+        self.jump_buffer_time = jump_buffer_time;
+        self
+    }
+
+    pub fn build(self) -> Result<Jumper, TuningError> {
+        Checks::new("Jumper")
+            .non_negative("jump_force", self.jump_force)
+            .between("release_multi", self.release_multi, 0f32, 1f32)
+            .non_negative("x_multi", self.x_multi)
+            .non_negative("coyote_time", self.coyote_time)
+            .non_negative("jump_buffer_time", self.jump_buffer_time)
+            .finish()?;
+        Ok(Jumper {
+            jump_force: self.jump_force,
+            release_multi: self.release_multi,
+            x_multi: self.x_multi,
+            coyote_time: Timer::from_seconds(self.coyote_time, TimerMode::Once),
+            jump_buffer: Timer::from_seconds(self.jump_buffer_time, TimerMode::Once),
+            can_release: false,
+        })
+    }
+}
+
+impl From<Jumper> for JumperBuilder {
+    fn from(jumper: Jumper) -> Self {
+        Self {
+            jump_force: jumper.jump_force,
+            release_multi: jumper.release_multi,
+            x_multi: jumper.x_multi,
+            coyote_time: jumper.coyote_time.duration().as_secs_f32(),
+            jump_buffer_time: jumper.jump_buffer.duration().as_secs_f32(),
+        }
+    }
+}
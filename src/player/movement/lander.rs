@@ -1,6 +1,10 @@
 use super::*;
 
-#[derive(Component, Clone, Debug, PartialEq, Reflect)]
+const LAND_TRAUMA: f32 = 0.5f32;
+
+#[derive(Component, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+#[serde(try_from = "LanderBuilder", into = "LanderBuilder")]
 pub struct Lander {
     pub land_buffer_timer: Timer,
     pub vel_boost: Vec2,
@@ -12,21 +16,96 @@ pub struct Lander {
 }
 
 impl Lander {
-    pub fn new(
-        land_buffer_time: f32,
-        vel_boost: Vec2,
-        vel_multi: f32,
-        vel_cap: f32,
-        min_land_vel: f32,
-    ) -> Self {
+    /// Takes the tuning values of `tuned`, a buffered land press and the fall before it are kept
+    pub fn retune(&mut self, tuned: &Self) {
+        self.land_buffer_timer
+            .set_duration(tuned.land_buffer_timer.duration());
+        self.vel_boost = tuned.vel_boost;
+        self.vel_multi = tuned.vel_multi;
+        self.vel_cap = tuned.vel_cap;
+        self.min_land_vel = tuned.min_land_vel;
+    }
+}
+
+tuned!(Lander, LanderBuilder);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct LanderBuilder {
+    land_buffer_time: f32,
+    vel_boost: Vec2,
+    vel_multi: f32,
+    vel_cap: f32,
+    min_land_vel: f32,
+}
+
+impl Default for LanderBuilder {
+    fn default() -> Self {
         Self {
-            land_buffer_timer: Timer::from_seconds(land_buffer_time, TimerMode::Once),
-            vel_boost,
-            vel_multi,
-            vel_cap,
-            min_land_vel,
+            land_buffer_time: 0.25f32,
+            vel_boost: Vec2::new(15f32, 0.8f32),
+            vel_multi: 1.2f32,
+            vel_cap: 700f32,
+            min_land_vel: -50f32,
+        }
+    }
+}
+
+impl LanderBuilder {
+    /// Seconds a land press is remembered for
+    pub fn land_buffer_time(mut self, land_buffer_time: f32) -> Self {
+        self.land_buffer_time = land_buffer_time;
+        self
+    }
+
+    pub fn vel_boost(mut self, vel_boost: Vec2) -> Self {
+        self.vel_boost = vel_boost;
+        self
+    }
+
+    pub fn vel_multi(mut self, vel_multi: f32) -> Self {
+        self.vel_multi = vel_multi;
+        self
+    }
+
+    pub fn vel_cap(mut self, vel_cap: f32) -> Self {
+        self.vel_cap = vel_cap;
+        self
+    }
+
+    /// Landing only counts when falling faster than this (it is negative)
+    pub fn min_land_vel(mut self, min_land_vel: f32) -> Self {
+        self.min_land_vel = min_land_vel;
+        self
+    }
+
+    pub fn build(self) -> Result<Lander, TuningError> {
+        Checks::new("Lander")
+            .non_negative("land_buffer_time", self.land_buffer_time)
+            .non_negative("vel_multi", self.vel_multi)
+            .non_negative("vel_cap", self.vel_cap)
+            .non_positive("min_land_vel", self.min_land_vel)
+            .finish()?;
+        Ok(Lander {
+            land_buffer_timer: Timer::from_seconds(self.land_buffer_time, TimerMode::Once),
+            vel_boost: self.vel_boost,
+            vel_multi: self.vel_multi,
+            vel_cap: self.vel_cap,
+            min_land_vel: self.min_land_vel,
             prev_state: None,
             prev_vel: 0f32,
+        })
+    }
+}
+
+impl From<Lander> for LanderBuilder {
+    fn from(lander: Lander) -> Self {
+        Self {
+            land_buffer_time: lander.land_buffer_timer.duration().as_secs_f32(),
+            vel_boost: lander.vel_boost,
+            vel_multi: lander.vel_multi,
+            vel_cap: lander.vel_cap,
+            min_land_vel: lander.min_land_vel,
         }
     }
 }
@@ -40,31 +119,33 @@ pub fn lander(
         Option<&GroundedState>,
         &KinematicCharacterControllerOutput,
     )>,
-    time: Res<Time>,
+    mut shake: EventWriter<CameraShake>,
+    time: Res<FixedTime>,
 ) {
     let end = |lander: &mut Mut<Lander>, s: Option<&InAirState>, vel: &Mut<KinematicVelocity>| {
-        lander.prev_state = s.map(|s| *s); // Do after all other state handling
+        lander.prev_state = s.copied(); // Do after all other state handling
         lander.prev_vel = vel.y;
     };
 
     for (mut lander, mut vel, input, in_air_state, grounded_state, output) in query.iter_mut() {
-        lander
-            .land_buffer_timer
-            .tick(Duration::from_secs_f32(time.delta_seconds()));
+        lander.land_buffer_timer.tick(time.period);
         if input.just_pressed(InputAction::Land) {
             lander.land_buffer_timer.reset();
         }
-        if !should_land(&lander, &input, &grounded_state) {
+        if !should_land(&lander, input, &grounded_state) {
             end(&mut lander, in_air_state, &vel);
             continue;
         }
         println!("Should land!");
 
-        let dir = get_landing_dir(&vel, &output);
+        let dir = get_landing_dir(&vel, output);
         let multi = get_landing_multi() * 1000f32;
 
         vel.x += (dir * multi).x;
         vel.y += (dir * multi).y;
+        shake.send(CameraShake {
+            trauma: LAND_TRAUMA,
+        });
 
         end(&mut lander, in_air_state, &vel)
     }
@@ -0,0 +1,257 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use super::*;
+use crate::player::replay::Replay;
+
+/// Where the presets are loaded from, every `*.movement.ron` file in it is a preset named after
+/// the file
+pub const PROFILES_DIR: &str = "movement";
+pub const DEFAULT_PROFILE: &str = "default";
+/// Switches to the next preset, except while a replay is recording or playing
+pub const PROFILE_KEY: KeyCode = KeyCode::F9;
+
+pub(in crate::player) struct MovementProfilePlugin;
+
+impl Plugin for MovementProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<MovementProfile>()
+            .init_asset_loader::<MovementProfileLoader>()
+            .insert_resource(MovementProfiles::default())
+            .add_systems(PreStartup, load)
+            .add_systems(
+                Update,
+                (cycle_profiles, apply_profile)
+                    .chain()
+                    .in_set(PlayerSet::Movement),
+            );
+    }
+}
+
+/// Every tuning value of the player's movement. The default is the [`DEFAULT_PROFILE`] preset,
+/// which the player spawns with
+#[derive(TypeUuid, TypePath, Deserialize, Clone, Debug, PartialEq)]
+#[uuid = "b7e2a4c9-3f1d-4e8a-a6c5-0d9f2e7b1c38"]
+pub struct MovementProfile {
+    pub gravity: Vec2,
+    pub horizontal: HorizontalMovement,
+    pub terminal_velocity: TerminalVelocity,
+    pub jumper: Jumper,
+    pub wall_movement: WallMovement,
+    pub slingshot: Slingshot,
+    /// No grappler if `None`
+    #[serde(default)]
+    pub grappler: Option<Grappler>,
+    /// No lander if `None`
+    #[serde(default)]
+    pub lander: Option<Lander>,
+}
+
+/// The default preset is built in as well, so the player can spawn before the presets have loaded
+const DEFAULT_PROFILE_RON: &str = include_str!("../../../assets/movement/default.movement.ron");
+
+impl Default for MovementProfile {
+    fn default() -> Self {
+        ron::from_str(DEFAULT_PROFILE_RON)
+            .unwrap_or_else(|e| panic!("The {DEFAULT_PROFILE} movement profile is invalid: {e}"))
+    }
+}
+
+#[derive(Default)]
+pub struct MovementProfileLoader;
+
+impl AssetLoader for MovementProfileLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let profile = ron::de::from_bytes::<MovementProfile>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(profile));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["movement.ron"]
+    }
+}
+
+/// The loaded presets by name, and which one the player uses. Setting `selected` applies it,
+/// and it is applied again whenever its file is modified. In any build [`PROFILE_KEY`] switches
+/// to the next preset, and recordings play back with the preset they were recorded with
+#[derive(Resource, Clone, Debug)]
+pub struct MovementProfiles {
+    pub selected: String,
+    handles: HashMap<String, Handle<MovementProfile>>,
+}
+
+impl Default for MovementProfiles {
+    fn default() -> Self {
+        Self {
+            selected: DEFAULT_PROFILE.to_string(),
+            handles: HashMap::new(),
+        }
+    }
+}
+
+impl MovementProfiles {
+    /// The names of the presets in alphabetical order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self
+            .handles
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<&str>>();
+        names.sort();
+        names
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Handle<MovementProfile>> {
+        self.handles.get(name)
+    }
+}
+
+fn profile_name(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy();
+    name.strip_suffix(".movement.ron").map(str::to_string)
+}
+
+fn load(asset_server: Res<AssetServer>, mut profiles: ResMut<MovementProfiles>) {
+    let paths = match asset_server
+        .asset_io()
+        .read_directory(Path::new(PROFILES_DIR))
+    {
+        Ok(p) => p.collect::<Vec<PathBuf>>(),
+        Err(e) => {
+            error!("Could not read the movement profiles folder: {e}");
+            return;
+        }
+    };
+
+    for path in paths {
+        if let Some(name) = profile_name(&path) {
+            let handle = asset_server.load(path);
+            profiles.handles.insert(name, handle);
+        }
+    }
+}
+
+/// Applies the selected profile to the player when it loads, is modified or another one is
+/// selected. The movement components keep their state, so this can happen mid jump
+fn apply_profile(
+    mut cmd: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &mut HorizontalMovement,
+            &mut TerminalVelocity,
+            &mut Jumper,
+            &mut WallMovement,
+            &mut Slingshot,
+            Option<&mut Grappler>,
+            Option<&mut Lander>,
+        ),
+        With<Player>,
+    >,
+    mut events: EventReader<AssetEvent<MovementProfile>>,
+    mut gravity: ResMut<KinematicGravity>,
+    profiles: Res<MovementProfiles>,
+    assets: Res<Assets<MovementProfile>>,
+) {
+    let handle = profiles.get(&profiles.selected);
+    let mut reloaded = false;
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle: h } | AssetEvent::Modified { handle: h } => {
+                reloaded |= Some(h) == handle
+            }
+            AssetEvent::Removed { .. } => continue,
+        }
+    }
+    if !reloaded && !profiles.is_changed() {
+        return;
+    }
+    let profile = match handle.map(|h| assets.get(h)) {
+        Some(Some(p)) => p,
+        // Applied once it has loaded
+        Some(None) => return,
+        None => {
+            warn!("There is no {} movement profile", profiles.selected);
+            return;
+        }
+    };
+
+    gravity.0 = profile.gravity;
+    for (
+        entity,
+        mut horizontal,
+        mut terminal,
+        mut jumper,
+        mut wall,
+        mut slingshot,
+        grappler,
+        lander,
+    ) in query.iter_mut()
+    {
+        *horizontal = profile.horizontal.clone();
+        *terminal = profile.terminal_velocity.clone();
+        jumper.retune(&profile.jumper);
+        wall.retune(&profile.wall_movement);
+        slingshot.retune(&profile.slingshot);
+
+        match (grappler, &profile.grappler) {
+            (Some(mut grappler), Some(tuned)) => grappler.retune(tuned),
+            (None, Some(tuned)) => {
+                cmd.entity(entity).insert(tuned.clone());
+            }
+            (Some(_), None) => {
+                cmd.entity(entity).remove::<Grappler>();
+            }
+            (None, None) => {}
+        }
+        match (lander, &profile.lander) {
+            (Some(mut lander), Some(tuned)) => lander.retune(tuned),
+            (None, Some(tuned)) => {
+                cmd.entity(entity).insert(tuned.clone());
+            }
+            (Some(_), None) => {
+                cmd.entity(entity).remove::<Lander>();
+            }
+            (None, None) => {}
+        }
+    }
+    info!("Using the {} movement profile", profiles.selected);
+}
+
+/// [`PROFILE_KEY`] switches to the next preset
+fn cycle_profiles(
+    mut profiles: ResMut<MovementProfiles>,
+    keys: Res<Input<KeyCode>>,
+    replay: Res<Replay>,
+) {
+    if !keys.just_pressed(PROFILE_KEY) {
+        return;
+    }
+    // Replays only save the preset they start with
+    if !matches!(*replay, Replay::Off) {
+        warn!("The movement profile can't be switched during a replay");
+        return;
+    }
+    let names = profiles.names();
+    let next = match names.iter().position(|n| *n == profiles.selected) {
+        Some(i) => names[(i + 1) % names.len()].to_string(),
+        None => match names.first() {
+            Some(n) => n.to_string(),
+            None => return,
+        },
+    };
+    profiles.selected = next;
+}
@@ -1,40 +1,53 @@
 use std::{collections::HashMap, time::Duration};
 
-use crate::{level::GrapplePoint, player::input::InputAction};
+use crate::{
+    level::GrapplePoint,
+    player::{camera::shake::CameraShake, input::InputAction},
+};
 
-use super::{jumper::Jumper, velocity::KinematicVelocity};
+use super::{
+    jumper::Jumper,
+    tuning::{tuned, Checks, TuningError},
+    velocity::KinematicVelocity,
+    GroundedState,
+};
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
 
-#[derive(Component, Clone, Debug, PartialEq, Reflect)]
+const LAUNCH_TRAUMA: f32 = 0.4f32;
+pub const GRAPPLE_TAP_TIME: f32 = 0.15f32;
+
+#[derive(Component, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+#[serde(try_from = "SlingshotBuilder", into = "SlingshotBuilder")]
 pub struct Slingshot {
     pub force: f32,
     pub range: f32,
     pub dir_multi: Vec2,
     pub above_multi: f32,
+    /// Releasing grapple within this many seconds of pressing it slingshots instead
+    pub grapple_tap_time: f32,
+    /// Seconds grapple has been held for, counted in fixed steps
+    grapple_held: f32,
     closest_point: Option<Entity>,
+    /// The point of the last launch, until landing
+    launched_from: Option<Entity>,
     buffer_timer: Timer,
     delay_timer: Timer,
 }
 
 impl Slingshot {
-    pub fn new(
-        force: f32,
-        range: f32,
-        dir_multi: Vec2,
-        above_multi: f32,
-        buffer_time: f32,
-        delay_time: f32,
-    ) -> Self {
-        Self {
-            force,
-            range,
-            dir_multi,
-            above_multi,
-            closest_point: None,
-            buffer_timer: Timer::from_seconds(buffer_time, TimerMode::Once),
-            delay_timer: Timer::from_seconds(delay_time, TimerMode::Once),
-        }
+    /// Takes the tuning values of `tuned`, without losing the point it launched from
+    pub fn retune(&mut self, tuned: &Self) {
+        self.force = tuned.force;
+        self.range = tuned.range;
+        self.dir_multi = tuned.dir_multi;
+        self.above_multi = tuned.above_multi;
+        self.grapple_tap_time = tuned.grapple_tap_time;
+        self.buffer_timer
+            .set_duration(tuned.buffer_timer.duration());
+        self.delay_timer.set_duration(tuned.delay_timer.duration());
     }
 
     pub fn can_slingshot(&self) -> bool {
@@ -44,6 +57,119 @@ impl Slingshot {
     pub fn get_closest(&self) -> Option<Entity> {
         self.closest_point
     }
+
+    pub fn launched_from(&self) -> Option<Entity> {
+        self.launched_from
+    }
+}
+
+tuned!(Slingshot, SlingshotBuilder);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct SlingshotBuilder {
+    force: f32,
+    range: f32,
+    dir_multi: Vec2,
+    above_multi: f32,
+    buffer_time: f32,
+    delay_time: f32,
+    grapple_tap_time: f32,
+}
+
+impl Default for SlingshotBuilder {
+    fn default() -> Self {
+        Self {
+            force: 750f32,
+            range: 250f32,
+            dir_multi: Vec2::new(1.35f32, 1f32),
+            above_multi: 0.5f32,
+            buffer_time: 0.5f32,
+            delay_time: 0.7f32,
+            grapple_tap_time: GRAPPLE_TAP_TIME,
+        }
+    }
+}
+
+impl SlingshotBuilder {
+    pub fn force(mut self, force: f32) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// How far away a grapple point can be
+    pub fn range(mut self, range: f32) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// Multiplies the launch direction
+    pub fn dir_multi(mut self, dir_multi: Vec2) -> Self {
+        self.dir_multi = dir_multi;
+        self
+    }
+
+    /// Multiplies the force when launching from above the point
+    pub fn above_multi(mut self, above_multi: f32) -> Self {
+        self.above_multi = above_multi;
+        self
+    }
+
+    /// Seconds a slingshot press is remembered for
+    pub fn buffer_time(mut self, buffer_time: f32) -> Self {
+        self.buffer_time = buffer_time;
+        self
+    }
+
+    /// Seconds between launches
+    pub fn delay_time(mut self, delay_time: f32) -> Self {
+        self.delay_time = delay_time;
+        self
+    }
+
+    pub fn grapple_tap_time(mut self, grapple_tap_time: f32) -> Self {
+        self.grapple_tap_time = grapple_tap_time;
+        self
+    }
+
+    pub fn build(self) -> Result<Slingshot, TuningError> {
+        Checks::new("Slingshot")
+            .non_negative("force", self.force)
+            .positive("range", self.range)
+            .non_negative("dir_multi.x", self.dir_multi.x)
+            .non_negative("dir_multi.y", self.dir_multi.y)
+            .non_negative("above_multi", self.above_multi)
+            .non_negative("buffer_time", self.buffer_time)
+            .non_negative("delay_time", self.delay_time)
+            .non_negative("grapple_tap_time", self.grapple_tap_time)
+            .finish()?;
+        Ok(Slingshot {
+            force: self.force,
+            range: self.range,
+            dir_multi: self.dir_multi,
+            above_multi: self.above_multi,
+            grapple_tap_time: self.grapple_tap_time,
+            grapple_held: 0f32,
+            closest_point: None,
+            launched_from: None,
+            buffer_timer: Timer::from_seconds(self.buffer_time, TimerMode::Once),
+            delay_timer: Timer::from_seconds(self.delay_time, TimerMode::Once),
+        })
+    }
+}
+
+impl From<Slingshot> for SlingshotBuilder {
+    fn from(slingshot: Slingshot) -> Self {
+        Self {
+            force: slingshot.force,
+            range: slingshot.range,
+            dir_multi: slingshot.dir_multi,
+            above_multi: slingshot.above_multi,
+            buffer_time: slingshot.buffer_timer.duration().as_secs_f32(),
+            delay_time: slingshot.delay_timer.duration().as_secs_f32(),
+            grapple_tap_time: slingshot.grapple_tap_time,
+        }
+    }
 }
 
 pub fn slingshot(
@@ -54,21 +180,32 @@ pub fn slingshot(
             &ActionState<InputAction>,
             &mut KinematicVelocity,
             Option<&mut Jumper>,
+            Option<&GroundedState>,
         ),
         Without<GrapplePoint>,
     >,
     points: Query<(Entity, &GlobalTransform), With<GrapplePoint>>,
-    time: Res<Time>,
+    mut shake: EventWriter<CameraShake>,
+    time: Res<FixedTime>,
 ) {
     let points_map = points.iter().collect::<HashMap<Entity, &GlobalTransform>>();
     let points_vec = points.iter().collect::<Vec<(Entity, &GlobalTransform)>>();
 
-    for (mut slingshot, transform, input, mut vel, jumper) in slingshot.iter_mut() {
+    for (mut slingshot, transform, input, mut vel, jumper, grounded) in slingshot.iter_mut() {
+        if grounded.is_some() {
+            slingshot.launched_from = None;
+        }
         get_closest_points((transform, &mut slingshot), &points_vec);
         buffer_time(&mut slingshot, input, &time);
 
         if !slingshot.buffer_timer.finished() && slingshot.can_slingshot() {
             slingshot_impulse(&mut slingshot, transform, &mut vel, &points_map, jumper);
+            // The closest point is cleared when the launch succeeds
+            if !slingshot.can_slingshot() {
+                shake.send(CameraShake {
+                    trauma: LAUNCH_TRAUMA,
+                });
+            }
         }
     }
 }
@@ -97,19 +234,27 @@ fn get_closest_points(
     slingshot.1.closest_point = closest.1.copied();
 }
 
-fn buffer_time(slingshot: &mut Mut<Slingshot>, input: &ActionState<InputAction>, time: &Res<Time>) {
-    slingshot
-        .buffer_timer
-        .tick(Duration::from_secs_f32(time.delta_seconds()));
+fn buffer_time(
+    slingshot: &mut Mut<Slingshot>,
+    input: &ActionState<InputAction>,
+    time: &Res<FixedTime>,
+) {
+    slingshot.buffer_timer.tick(time.period);
+    if input.just_pressed(InputAction::Grapple) {
+        slingshot.grapple_held = 0f32;
+    } else if input.pressed(InputAction::Grapple) {
+        slingshot.grapple_held += time.period.as_secs_f32();
+    }
 
     if !slingshot.delay_timer.finished() {
-        slingshot
-            .delay_timer
-            .tick(Duration::from_secs_f32(time.delta_seconds()));
+        slingshot.delay_timer.tick(time.period);
         return;
     }
 
-    if input.just_pressed(InputAction::Slingshot) {
+    // Tapping grapple is the same as pressing slingshot
+    let grapple_tapped = input.just_released(InputAction::Grapple)
+        && slingshot.grapple_held < slingshot.grapple_tap_time;
+    if input.just_pressed(InputAction::Slingshot) || grapple_tapped {
         slingshot.buffer_timer.reset();
     }
 }
@@ -131,6 +276,7 @@ fn slingshot_impulse(
 
     // Set variables
     slingshot.buffer_timer.tick(Duration::from_secs(1000));
+    slingshot.launched_from = slingshot.closest_point;
     slingshot.closest_point = None;
     slingshot.delay_timer.reset();
 
@@ -1,6 +1,7 @@
 use super::*;
 
-#[derive(Default, Component, Clone, Debug, PartialEq, Reflect)]
+#[derive(Component, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(try_from = "TerminalVelocityBuilder", into = "TerminalVelocityBuilder")]
 pub struct TerminalVelocity {
     pub base_val: f32,
     pub hold_down_multi: f32,
@@ -33,12 +34,63 @@ pub fn terminal_velocity(
     }
 }
 
-impl TerminalVelocity {
-    pub fn new(base_val: f32, hold_down_multi: f32, wall_slide_multi: f32) -> Self {
+tuned!(TerminalVelocity, TerminalVelocityBuilder);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct TerminalVelocityBuilder {
+    base_val: f32,
+    hold_down_multi: f32,
+    wall_slide_multi: f32,
+}
+
+impl Default for TerminalVelocityBuilder {
+    fn default() -> Self {
+        Self {
+            base_val: -1150f32,
+            hold_down_multi: 1.5f32,
+            wall_slide_multi: 0.1f32,
+        }
+    }
+}
+
+impl TerminalVelocityBuilder {
+    /// The fastest the player can fall (it is negative)
+    pub fn base_val(mut self, base_val: f32) -> Self {
+        self.base_val = base_val;
+        self
+    }
+
+    pub fn hold_down_multi(mut self, hold_down_multi: f32) -> Self {
+        self.hold_down_multi = hold_down_multi;
+        self
+    }
+
+    pub fn wall_slide_multi(mut self, wall_slide_multi: f32) -> Self {
+        self.wall_slide_multi = wall_slide_multi;
+        self
+    }
+
+    pub fn build(self) -> Result<TerminalVelocity, TuningError> {
+        Checks::new("TerminalVelocity")
+            .non_positive("base_val", self.base_val)
+            .non_negative("hold_down_multi", self.hold_down_multi)
+            .non_negative("wall_slide_multi", self.wall_slide_multi)
+            .finish()?;
+        Ok(TerminalVelocity {
+            base_val: self.base_val,
+            hold_down_multi: self.hold_down_multi,
+            wall_slide_multi: self.wall_slide_multi,
+        })
+    }
+}
+
+impl From<TerminalVelocity> for TerminalVelocityBuilder {
+    fn from(terminal_vel: TerminalVelocity) -> Self {
         Self {
-            base_val,
-            hold_down_multi,
-            wall_slide_multi,
+            base_val: terminal_vel.base_val,
+            hold_down_multi: terminal_vel.hold_down_multi,
+            wall_slide_multi: terminal_vel.wall_slide_multi,
         }
     }
 }
@@ -0,0 +1,129 @@
+use std::fmt;
+
+/// Tuning values that are out of range, returned by the builders of the movement components (and
+/// when deserializing them)
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuningError {
+    pub component: &'static str,
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for TuningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid {}: {}",
+            self.component,
+            self.problems.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for TuningError {}
+
+/// Collects every problem with the values of a component, so they can be reported at once.
+/// The comparisons are written so NaN fails them, and values that become timers or are divided by
+/// have to be finite too
+pub(super) struct Checks {
+    component: &'static str,
+    problems: Vec<String>,
+}
+
+#[allow(clippy::neg_cmp_op_on_partial_ord)]
+impl Checks {
+    pub fn new(component: &'static str) -> Self {
+        Self {
+            component,
+            problems: Vec::new(),
+        }
+    }
+
+    pub fn non_negative(mut self, field: &str, value: f32) -> Self {
+        if !(value >= 0f32) {
+            self.problems
+                .push(format!("{field} can't be negative ({value})"));
+        } else if !value.is_finite() {
+            self.problems.push(format!("{field} has to be finite"));
+        }
+        self
+    }
+
+    /// For values that are divided by
+    pub fn positive(mut self, field: &str, value: f32) -> Self {
+        if !(value > 0f32) {
+            self.problems
+                .push(format!("{field} has to be more than 0 ({value})"));
+        } else if !value.is_finite() {
+            self.problems.push(format!("{field} has to be finite"));
+        }
+        self
+    }
+
+    pub fn non_positive(mut self, field: &str, value: f32) -> Self {
+        if !(value <= 0f32) {
+            self.problems
+                .push(format!("{field} can't be positive ({value})"));
+        }
+        self
+    }
+
+    pub fn between(mut self, field: &str, value: f32, min: f32, max: f32) -> Self {
+        if !(min..=max).contains(&value) {
+            self.problems
+                .push(format!("{field} has to be from {min} to {max} ({value})"));
+        }
+        self
+    }
+
+    /// `low` can't be more than `high`
+    pub fn ordered(mut self, low: (&str, f32), high: (&str, f32)) -> Self {
+        if !(low.1 <= high.1) {
+            self.problems.push(format!(
+                "{} ({}) can't be more than {} ({})",
+                low.0, low.1, high.0, high.1
+            ));
+        }
+        self
+    }
+
+    pub fn finish(self) -> Result<(), TuningError> {
+        match self.problems.is_empty() {
+            true => Ok(()),
+            false => Err(TuningError {
+                component: self.component,
+                problems: self.problems,
+            }),
+        }
+    }
+}
+
+/// The parts of a tuned component that only depend on its builder: `builder()`, `Default` with
+/// the default tuning, and `TryFrom` the builder, which is how the component is deserialized. The
+/// builder needs `Default` and a `build` that returns `Result<$component, TuningError>`
+macro_rules! tuned {
+    ($component:ident, $builder:ident) => {
+        impl $component {
+            pub fn builder() -> $builder {
+                $builder::default()
+            }
+        }
+
+        impl Default for $component {
+            fn default() -> Self {
+                $builder::default()
+                    .build()
+                    .unwrap_or_else(|e| panic!("the default tuning should be valid: {e}"))
+            }
+        }
+
+        impl TryFrom<$builder> for $component {
+            type Error = TuningError;
+
+            fn try_from(builder: $builder) -> Result<Self, Self::Error> {
+                builder.build()
+            }
+        }
+    };
+}
+
+pub(super) use tuned;
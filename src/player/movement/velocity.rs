@@ -57,18 +57,30 @@ impl std::ops::AddAssign<Vec2> for KinematicVelocity {
     }
 }
 
-pub fn kinematic_velocity(
+/// Stops the velocity against whatever the character controller ran into last step, before any
+/// forces are added to it
+pub fn kinematic_collisions(
     mut query: Query<(
         &mut KinematicVelocity,
-        &mut Transform,
-        Option<&mut KinematicCharacterController>,
         Option<&KinematicCharacterControllerOutput>,
     )>,
-    time: Res<Time>,
 ) {
-    for (mut vel, mut transform, controller, output) in query.iter_mut() {
+    for (mut vel, output) in query.iter_mut() {
         kinematic_velocity_collision_check(&mut vel, &output);
-        let translation = vel.to_vec2() * time.delta_seconds();
+    }
+}
+
+/// Moves by the velocity once every force has been added to it
+pub fn kinematic_velocity(
+    mut query: Query<(
+        &KinematicVelocity,
+        &mut Transform,
+        Option<&mut KinematicCharacterController>,
+    )>,
+    time: Res<FixedTime>,
+) {
+    for (vel, mut transform, controller) in query.iter_mut() {
+        let translation = vel.to_vec2() * time.period.as_secs_f32();
         match controller {
             Some(mut controller) => controller.translation = Some(translation),
             None => transform.translation += translation.extend(0f32),
@@ -2,7 +2,9 @@ use super::*;
 
 /// The i8s indicate where the wall is in relation to the player (-1 is to the left, and 1 is to
 /// the right)
-#[derive(Default, Component, Clone, Debug, PartialEq, Reflect)]
+#[derive(Component, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+#[serde(try_from = "WallMovementBuilder", into = "WallMovementBuilder")]
 pub struct WallMovement {
     pub walljump_force: Vec2,
     pub walljump_y_range: (f32, f32),
@@ -21,7 +23,7 @@ pub fn wall_movement(
     )>,
     rb_query: Query<(Entity, Option<&RigidBody>)>,
     ctx: Res<RapierContext>,
-    time: Res<Time>,
+    time: Res<FixedTime>,
 ) {
     let bodies = rb_query
         .iter()
@@ -41,11 +43,8 @@ pub fn wall_movement(
     }
 }
 
-fn coyote_timer(wall_mover: &mut Mut<WallMovement>, time: &Res<Time>) {
-    wall_mover
-        .coyote_time
-        .0
-        .tick(Duration::from_secs_f32(time.delta_seconds()));
+fn coyote_timer(wall_mover: &mut Mut<WallMovement>, time: &Res<FixedTime>) {
+    wall_mover.coyote_time.0.tick(time.period);
     if let Some((_, i)) = wall_mover.current_wall {
         wall_mover.coyote_time.0.reset();
         wall_mover.coyote_time.1 = i;
@@ -105,19 +104,15 @@ fn get_walls(
 }
 
 impl WallMovement {
-    pub fn new(
-        walljump_force: Vec2,
-        walljump_y_range: (f32, f32),
-        max_wall_toi: f32,
-        coyote_time: f32,
-    ) -> Self {
-        Self {
-            walljump_force,
-            walljump_y_range,
-            max_wall_toi,
-            current_wall: None,
-            coyote_time: (Timer::from_seconds(coyote_time, TimerMode::Once), 0),
-        }
+    /// Takes the tuning values of `tuned`, it stays on the wall it is on and the coyote time keeps
+    /// running
+    pub fn retune(&mut self, tuned: &Self) {
+        self.walljump_force = tuned.walljump_force;
+        self.walljump_y_range = tuned.walljump_y_range;
+        self.max_wall_toi = tuned.max_wall_toi;
+        self.coyote_time
+            .0
+            .set_duration(tuned.coyote_time.0.duration());
     }
 
     pub fn coyote_timer_remaining(&self) -> f32 {
@@ -136,3 +131,80 @@ impl WallMovement {
         self.current_wall
     }
 }
+
+tuned!(WallMovement, WallMovementBuilder);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct WallMovementBuilder {
+    walljump_force: Vec2,
+    walljump_y_range: (f32, f32),
+    max_wall_toi: f32,
+    coyote_time: f32,
+}
+
+impl Default for WallMovementBuilder {
+    fn default() -> Self {
+        Self {
+            walljump_force: Vec2::new(450f32, 350f32),
+            walljump_y_range: (-20f32, 20f32),
+            max_wall_toi: 0.25f32,
+            coyote_time: 0.2f32,
+        }
+    }
+}
+
+impl WallMovementBuilder {
+    /// The x is pushed away from the wall
+    pub fn walljump_force(mut self, walljump_force: Vec2) -> Self {
+        self.walljump_force = walljump_force;
+        self
+    }
+
+    /// The part of the player (relative to its center) that has to be next to a wall
+    pub fn walljump_y_range(mut self, min: f32, max: f32) -> Self {
+        self.walljump_y_range = (min, max);
+        self
+    }
+
+    /// How far away a wall can be
+    pub fn max_wall_toi(mut self, max_wall_toi: f32) -> Self {
+        self.max_wall_toi = max_wall_toi;
+        self
+    }
+
+    /// Seconds after leaving a wall that walljumping still works
+    pub fn coyote_time(mut self, coyote_time: f32) -> Self {
+        self.coyote_time = coyote_time;
+        self
+    }
+
+    pub fn build(self) -> Result<WallMovement, TuningError> {
+        let (min, max) = self.walljump_y_range;
+        Checks::new("WallMovement")
+            .non_negative("walljump_force.x", self.walljump_force.x)
+            .non_negative("walljump_force.y", self.walljump_force.y)
+            .ordered(("walljump_y_range.0", min), ("walljump_y_range.1", max))
+            .non_negative("max_wall_toi", self.max_wall_toi)
+            .non_negative("coyote_time", self.coyote_time)
+            .finish()?;
+        Ok(WallMovement {
+            walljump_force: self.walljump_force,
+            walljump_y_range: self.walljump_y_range,
+            max_wall_toi: self.max_wall_toi,
+            current_wall: None,
+            coyote_time: (Timer::from_seconds(self.coyote_time, TimerMode::Once), 0),
+        })
+    }
+}
+
+impl From<WallMovement> for WallMovementBuilder {
+    fn from(wall_mover: WallMovement) -> Self {
+        Self {
+            walljump_force: wall_mover.walljump_force,
+            walljump_y_range: wall_mover.walljump_y_range,
+            max_wall_toi: wall_mover.max_wall_toi,
+            coyote_time: wall_mover.coyote_time.0.duration().as_secs_f32(),
+        }
+    }
+}
@@ -0,0 +1,512 @@
+use std::{fs, time::Duration};
+
+use bevy::{
+    ecs::entity::EntityMap,
+    scene::{serde::SceneDeserializer, DynamicSceneBuilder, SceneFilter},
+    time::TimeUpdateStrategy,
+};
+use bevy_rapier2d::prelude::KinematicCharacterControllerOutput;
+use leafwing_input_manager::{buttonlike::ButtonState, plugin::InputManagerSystem, prelude::*};
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+
+use super::{
+    input::{latch::LatchedInput, InputAction},
+    movement::{
+        grappler::Grappler, jumper::Jumper, lander::Lander, profile::MovementProfiles,
+        slingshot::Slingshot, velocity::KinematicVelocity, wall_movement::WallMovement,
+    },
+    state_machine::states::{GroundedState, InAirState, WallState},
+    Player,
+};
+use crate::{physics::Interpolated, DEBUG};
+use bevy::prelude::*;
+
+/// Where recordings are saved and played back from
+pub const REPLAY_FILE: &str = "replay.ron";
+
+/// Recorded and replayed positions further apart than this have diverged
+const DIVERGENCE_TOLERANCE: f32 = 0.01f32;
+/// How many frames PageUp and PageDown seek
+const SEEK_STEP: usize = 60;
+/// How many recorded frames are played per update while seeking
+const SEEK_FRAMES_PER_UPDATE: usize = 30;
+
+pub(super) struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        // Input is recorded and replaced before the fixed updates of the frame, which read it
+        app.init_resource::<Replay>().add_systems(
+            PreUpdate,
+            (fast_forward, replay).chain().after(InputManagerSystem::Update),
+        );
+        if DEBUG {
+            app.add_systems(
+                PreUpdate,
+                replay_controls
+                    .after(InputManagerSystem::Update)
+                    .before(fast_forward),
+            );
+        }
+    }
+}
+
+/// The input of every frame, along with where the player was before the frame
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Recording {
+    pub start_position: Vec2,
+    pub start_velocity: Vec2,
+    /// Time in seconds towards the next fixed update when the recording started
+    #[serde(default)]
+    pub start_accumulated: f32,
+    /// The rest of the movement state of the player when the recording started, from
+    /// [`save_player_state`]
+    #[serde(default)]
+    pub start_state: String,
+    /// If the player was on the ground, the state machine reads it before the first fixed update
+    #[serde(default)]
+    pub start_grounded: bool,
+    /// The movement preset, it can't be switched while recording
+    #[serde(default)]
+    pub profile: Option<String>,
+    pub frames: Vec<RecordedFrame>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecordedFrame {
+    /// Delta time in seconds
+    pub dt: f32,
+    pub actions: ActionState<InputAction>,
+    pub position: Vec2,
+}
+
+impl Recording {
+    pub fn load() -> Result<Self, String> {
+        let text = fs::read_to_string(REPLAY_FILE)
+            .map_err(|e| format!("Could not read {REPLAY_FILE}: {e}"))?;
+        ron::from_str(&text).map_err(|e| format!("Could not parse {REPLAY_FILE}: {e}"))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let text = ron::to_string(self).map_err(|e| format!("Could not serialize replay: {e}"))?;
+        fs::write(REPLAY_FILE, text).map_err(|e| format!("Could not write {REPLAY_FILE}: {e}"))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Playback {
+    pub recording: Recording,
+    /// The next frame to play
+    frame: usize,
+    pub paused: bool,
+    step: bool,
+    seek: Option<usize>,
+    /// Goes back to the start of the recording on the next paused frame
+    reset: bool,
+    /// The first frame where the player wasn't where it was when recording
+    diverged: Option<usize>,
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            frame: 0,
+            paused: false,
+            step: false,
+            seek: None,
+            // The player has to be where it was when the recording started
+            reset: true,
+            diverged: None,
+        }
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn diverged(&self) -> Option<usize> {
+        self.diverged
+    }
+
+    /// Plays one frame while paused
+    pub fn step(&mut self) {
+        self.step = true;
+    }
+
+    /// Seeking backwards restarts the recording, the frames up to `frame` are played
+    /// [`SEEK_FRAMES_PER_UPDATE`] at a time
+    pub fn seek(&mut self, frame: usize) {
+        let frame = frame.min(self.recording.frames.len());
+        self.reset |= frame < self.frame;
+        self.seek = Some(frame);
+    }
+
+    /// If the next frame plays, the player is reset in a frame that doesn't play so no time
+    /// passes between resetting and playing the first frame. Seeking plays frames in updates
+    /// that don't play one
+    fn advancing(&self) -> bool {
+        !self.reset && !self.seeking() && (!self.paused || self.step)
+    }
+
+    fn seeking(&self) -> bool {
+        self.seek.is_some_and(|s| s > self.frame)
+    }
+
+    /// Plays `frames[frame]`, warns the first time the player isn't where it was when recording
+    fn play(&mut self, position: Vec2) -> &RecordedFrame {
+        let frame = &self.recording.frames[self.frame];
+        if self.diverged.is_none() && position.distance(frame.position) > DIVERGENCE_TOLERANCE {
+            warn!(
+                "Replay diverged on frame {}, the player is at {position} instead of {}",
+                self.frame, frame.position
+            );
+            self.diverged = Some(self.frame);
+        }
+        self.frame += 1;
+        frame
+    }
+}
+
+/// The components of the player that [`save_player_state`] saves, its state and the timers and
+/// points of its movement
+fn player_state_filter() -> SceneFilter {
+    let mut filter = SceneFilter::deny_all();
+    filter
+        .allow::<GroundedState>()
+        .allow::<InAirState>()
+        .allow::<WallState>()
+        .allow::<Jumper>()
+        .allow::<WallMovement>()
+        .allow::<Grappler>()
+        .allow::<Slingshot>()
+        .allow::<Lander>();
+    filter
+}
+
+/// Serializes the movement state of the player besides its position and velocity
+pub fn save_player_state(world: &World, player: Entity) -> Result<String, String> {
+    let mut builder = DynamicSceneBuilder::from_world(world);
+    builder
+        .with_filter(player_state_filter())
+        .extract_entity(player);
+    builder
+        .build()
+        .serialize_ron(world.resource::<AppTypeRegistry>())
+        .map_err(|e| format!("Could not serialize the player state: {e}"))
+}
+
+/// Puts the player back in a state from [`save_player_state`]
+pub fn load_player_state(world: &mut World, player: Entity, state: &str) -> Result<(), String> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let scene = {
+        let mut deserializer = ron::de::Deserializer::from_str(state)
+            .map_err(|e| format!("Could not parse the player state: {e}"))?;
+        SceneDeserializer {
+            type_registry: &registry.read(),
+        }
+        .deserialize(&mut deserializer)
+        .map_err(|e| format!("Could not parse the player state: {e}"))?
+    };
+
+    // Only one of the states can be on the player, and the optional movement components might not
+    // have been there
+    {
+        let registry = registry.read();
+        let mut entity = world.entity_mut(player);
+        for type_id in player_state_filter().iter() {
+            if let Some(component) = registry
+                .get(*type_id)
+                .and_then(|r| r.data::<ReflectComponent>())
+            {
+                component.remove(&mut entity);
+            }
+        }
+    }
+    let mut entity_map = EntityMap::default();
+    for entity in scene.entities.iter() {
+        entity_map.insert(entity.entity, player);
+    }
+    scene
+        .write_to_world_with(world, &mut entity_map, &registry)
+        .map_err(|e| format!("Could not load the player state: {e}"))
+}
+
+/// Records the input of the player into a [`Recording`], or plays one back instead of the real
+/// input. The delta time of recorded frames is played back as well, so playback is deterministic
+#[derive(Resource, Clone, Debug, Default)]
+pub enum Replay {
+    #[default]
+    Off,
+    Recording(Recording),
+    Playing(Playback),
+}
+
+/// Where the physics has the player, the transform is interpolated between fixed updates
+fn physics_position(transform: &Transform, interpolated: Option<&Interpolated>) -> Vec2 {
+    match interpolated {
+        Some(i) => i.current().truncate(),
+        None => transform.translation.truncate(),
+    }
+}
+
+fn replay(
+    mut cmd: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &mut ActionState<InputAction>,
+            &mut Transform,
+            Option<&Interpolated>,
+            Option<&mut KinematicVelocity>,
+            Option<&mut LatchedInput>,
+            Option<&mut KinematicCharacterControllerOutput>,
+        ),
+        With<Player>,
+    >,
+    mut replay: ResMut<Replay>,
+    mut profiles: ResMut<MovementProfiles>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+    mut time: ResMut<Time>,
+    mut fixed_time: ResMut<FixedTime>,
+) {
+    let (player, mut actions, mut transform, interpolated, vel, latched, output) =
+        match query.get_single_mut() {
+            Ok(p) => p,
+            Err(_) => return,
+        };
+
+    let playback = match replay.as_mut() {
+        Replay::Off => return,
+        Replay::Recording(recording) => {
+            recording.frames.push(RecordedFrame {
+                dt: time.delta_seconds(),
+                actions: actions.clone(),
+                position: physics_position(&transform, interpolated),
+            });
+            return;
+        }
+        Replay::Playing(p) => p,
+    };
+
+    if time.is_paused() || playback.reset {
+        if playback.reset && time.is_paused() {
+            let recording = &playback.recording;
+            transform.translation = recording.start_position.extend(transform.translation.z);
+            if let Some(mut vel) = vel {
+                *vel = recording.start_velocity.into();
+            }
+            if let Some(mut latched) = latched {
+                latched.clear();
+            }
+            if let Some(mut output) = output {
+                output.grounded = recording.start_grounded;
+            }
+            // Applied in the update of this frame, which doesn't run any fixed updates
+            if let Some(profile) = &recording.profile {
+                if profiles.selected != *profile {
+                    profiles.selected = profile.clone();
+                }
+            }
+            // Recordings from before the state was saved only reset the position and velocity
+            if !recording.start_state.is_empty() {
+                let state = recording.start_state.clone();
+                cmd.add(move |world: &mut World| {
+                    if let Err(e) = load_player_state(world, player, &state) {
+                        warn!("{e}");
+                    }
+                });
+            }
+            *fixed_time = FixedTime::new(fixed_time.period);
+            fixed_time.tick(Duration::from_secs_f32(recording.start_accumulated));
+            playback.frame = 0;
+            playback.diverged = None;
+            playback.reset = false;
+        }
+
+        // No time passes this frame, so the real input is replaced by the last played frame
+        // without any presses or releases
+        *actions = match playback.frame.checked_sub(1) {
+            Some(i) => playback.recording.frames[i].actions.clone(),
+            None => ActionState::default(),
+        };
+        for action in InputAction::variants() {
+            let data = actions.action_data_mut(action);
+            data.state = match data.state {
+                ButtonState::JustPressed | ButtonState::Pressed => ButtonState::Pressed,
+                ButtonState::JustReleased | ButtonState::Released => ButtonState::Released,
+            };
+        }
+    } else {
+        *actions = playback
+            .play(physics_position(&transform, interpolated))
+            .actions
+            .clone();
+        playback.step = false;
+    }
+    if !playback.reset && playback.seek.is_some_and(|s| s <= playback.frame) {
+        playback.seek = None;
+        playback.paused = true;
+    }
+
+    // Decides if the next frame plays, and how long it is
+    let next = match playback.recording.frames.get(playback.frame) {
+        Some(f) => f,
+        None => {
+            info!("Replay finished");
+            *time_strategy = TimeUpdateStrategy::Automatic;
+            time.unpause();
+            *replay = Replay::Off;
+            return;
+        }
+    };
+    *time_strategy = TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(next.dt));
+    match playback.advancing() {
+        true => time.unpause(),
+        false => time.pause(),
+    }
+}
+
+/// Plays the frames up to where playback is seeking to, without waiting for an update per frame.
+/// Only runs in updates where time is paused, so no other frame plays
+fn fast_forward(world: &mut World) {
+    if !world.resource::<Time>().is_paused() {
+        return;
+    }
+    let player = match world
+        .query_filtered::<Entity, With<Player>>()
+        .get_single(world)
+    {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+
+    for _ in 0..SEEK_FRAMES_PER_UPDATE {
+        let position = {
+            let entity = world.entity(player);
+            physics_position(entity.get::<Transform>().unwrap(), entity.get::<Interpolated>())
+        };
+        let frame = match world.resource_mut::<Replay>().as_mut() {
+            Replay::Playing(p) if !p.reset && p.seeking() => p.play(position).clone(),
+            _ => return,
+        };
+
+        let mut entity = world.entity_mut(player);
+        if let Some(mut actions) = entity.get_mut::<ActionState<InputAction>>() {
+            *actions = frame.actions;
+        }
+        // The same as the fixed update loop of bevy would do with the recorded delta time
+        world
+            .resource_mut::<FixedTime>()
+            .tick(Duration::from_secs_f32(frame.dt));
+        while world.resource_mut::<FixedTime>().expend().is_ok() {
+            world.run_schedule(FixedUpdate);
+        }
+
+        let mut entity = world.entity_mut(player);
+        if let Some(actions) = entity.get::<ActionState<InputAction>>().cloned() {
+            if let Some(mut latched) = entity.get_mut::<LatchedInput>() {
+                latched.latch(&actions);
+            }
+        }
+    }
+}
+
+/// F5 starts and stops recording, F6 starts and stops playback, F7 pauses, F8 steps, Home
+/// restarts and PageUp/PageDown seek
+fn replay_controls(
+    mut cmd: Commands,
+    player_query: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&Interpolated>,
+            Option<&KinematicVelocity>,
+            Option<&KinematicCharacterControllerOutput>,
+        ),
+        With<Player>,
+    >,
+    mut replay: ResMut<Replay>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+    mut time: ResMut<Time>,
+    fixed_time: Res<FixedTime>,
+    profiles: Res<MovementProfiles>,
+    keys: Res<Input<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::F5) {
+        match replay.as_ref() {
+            Replay::Recording(recording) => {
+                match recording.save() {
+                    Ok(()) => info!("Saved {} frames to {REPLAY_FILE}", recording.frames.len()),
+                    Err(e) => warn!("{e}"),
+                }
+                *replay = Replay::Off;
+            }
+            _ => {
+                let (player, transform, interpolated, vel, output) =
+                    match player_query.get_single() {
+                        Ok(p) => p,
+                        Err(_) => return,
+                    };
+                info!("Recording");
+                *replay = Replay::Recording(Recording {
+                    start_position: physics_position(transform, interpolated),
+                    start_velocity: vel.map(|v| Vec2::from(*v)).unwrap_or_default(),
+                    start_accumulated: fixed_time.accumulated().as_secs_f32(),
+                    start_state: String::new(),
+                    start_grounded: output.is_some_and(|o| o.grounded),
+                    profile: Some(profiles.selected.clone()),
+                    frames: Vec::new(),
+                });
+                // Saving the state needs the whole world, nothing moves before the commands apply
+                cmd.add(move |world: &mut World| {
+                    let state = save_player_state(world, player);
+                    if let Replay::Recording(recording) = world.resource_mut::<Replay>().as_mut() {
+                        match state {
+                            Ok(state) => recording.start_state = state,
+                            Err(e) => warn!("{e}"),
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    if keys.just_pressed(KeyCode::F6) {
+        match replay.as_ref() {
+            Replay::Playing(_) => {
+                info!("Stopped replay");
+                *time_strategy = TimeUpdateStrategy::Automatic;
+                time.unpause();
+                *replay = Replay::Off;
+            }
+            _ => match Recording::load() {
+                Ok(recording) => {
+                    info!("Playing {} frames", recording.frames.len());
+                    *replay = Replay::Playing(Playback::new(recording));
+                }
+                Err(e) => warn!("{e}"),
+            },
+        }
+    }
+
+    let playback = match replay.as_mut() {
+        Replay::Playing(p) => p,
+        _ => return,
+    };
+    if keys.just_pressed(KeyCode::F7) {
+        playback.paused = !playback.paused;
+    }
+    if keys.just_pressed(KeyCode::F8) {
+        playback.step();
+    }
+    if keys.just_pressed(KeyCode::Home) {
+        playback.seek(0);
+    }
+    if keys.just_pressed(KeyCode::PageUp) {
+        playback.seek(playback.frame + SEEK_STEP);
+    }
+    if keys.just_pressed(KeyCode::PageDown) {
+        playback.seek(playback.frame.saturating_sub(SEEK_STEP));
+    }
+}
@@ -1,5 +1,5 @@
 use super::{Player, PlayerSet};
-use bevy::prelude::*;
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 
 use seldom_state::prelude::*;
 use states::*;
@@ -9,13 +9,37 @@ pub(super) struct StateMachinePlugin;
 
 impl Plugin for StateMachinePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, init.in_set(PlayerSet::StateMachine))
+        // seldom_state only adds its systems to `PostUpdate`, so they are taken from there and run
+        // in a schedule of their own
+        let mut machine_app = App::empty();
+        state_machine_plugin(&mut machine_app);
+        let schedule = machine_app
+            .world
+            .resource_mut::<Schedules>()
+            .remove(&PostUpdate)
+            .expect("seldom_state should add its systems to PostUpdate");
+
+        app.add_schedule(StateMachineSchedule, schedule)
+            .add_systems(
+                FixedUpdate,
+                run_state_machines.in_set(PlayerSet::StateMachine),
+            )
+            .add_systems(Startup, init.in_set(PlayerSet::StateMachine))
             .register_type::<GroundedState>()
             .register_type::<InAirState>()
             .register_type::<WallState>();
     }
 }
 
+/// The transitions of the state machines, they run in [`FixedUpdate`] so the states change at the
+/// same rate as the movement that reads them
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+struct StateMachineSchedule;
+
+fn run_state_machines(world: &mut World) {
+    world.run_schedule(StateMachineSchedule);
+}
+
 fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>) {
     cmd.entity(player_query.single()).insert((
         GroundedState::Idle,
@@ -70,25 +94,31 @@ fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>) {
 pub mod states {
     use bevy::prelude::*;
 
-    #[derive(Clone, Copy, Component, Reflect, PartialEq, PartialOrd, Debug)]
+    #[derive(Clone, Copy, Component, Reflect, PartialEq, PartialOrd, Debug, Default)]
     #[component(storage = "SparseSet")]
+    #[reflect(Component)]
     pub enum GroundedState {
+        #[default]
         Idle,
         Walking,
         Jumping,
     }
 
-    #[derive(Clone, Copy, Component, Reflect, PartialEq, PartialOrd, Debug)]
+    #[derive(Clone, Copy, Component, Reflect, PartialEq, PartialOrd, Debug, Default)]
     #[component(storage = "SparseSet")]
+    #[reflect(Component)]
     pub enum InAirState {
+        #[default]
         Rising,
         Falling,
         Grapple,
     }
 
-    #[derive(Clone, Copy, Component, Reflect, PartialEq, PartialOrd, Debug)]
+    #[derive(Clone, Copy, Component, Reflect, PartialEq, PartialOrd, Debug, Default)]
     #[component(storage = "SparseSet")]
+    #[reflect(Component)]
     pub enum WallState {
+        #[default]
         Rising,
         Sliding,
         Jumping,
@@ -1,4 +1,4 @@
-use crate::sprites::SpriteSheets;
+use crate::sprites::NamedSprite;
 
 use super::{Player, PlayerSet};
 use bevy::prelude::*;
@@ -11,23 +11,13 @@ impl Plugin for VisualsPlugin {
     }
 }
 
-fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>, sheets: Res<SpriteSheets>) {
-    let atlas = &sheets.0.get("player_sheet").unwrap().atlas;
-    let idx = sheets
-        .0
-        .get("player_sheet")
-        .unwrap()
-        .info
-        .get("p1_stand")
-        .unwrap()
-        .0;
-
+fn init(mut cmd: Commands, player_query: Query<Entity, With<Player>>) {
     cmd.entity(player_query.single()).insert((
         TextureAtlasSprite {
             custom_size: Some((25f32, 50f32).into()),
-            index: idx,
             ..Default::default()
         },
-        atlas.clone(),
+        Handle::<TextureAtlas>::default(),
+        NamedSprite::new("player_sheet", "p1_stand"),
     ));
 }
@@ -1,86 +1,437 @@
-use std::{collections::HashMap, error::Error};
+use std::{
+    collections::HashMap,
+    error::Error,
+    path::{Path, PathBuf},
+};
 
-use bevy::prelude::*;
+use bevy::{
+    asset::{AssetIoError, AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    render::texture::{CompressedImageFormats, ImageType, TextureError},
+    utils::BoxedFuture,
+};
+
+pub const SPRITES_DIR: &str = "sprites";
 
 pub(super) struct SpritesPlugin;
 
 impl Plugin for SpritesPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(SpriteSheets::default())
-            .add_systems(PreStartup, load);
+            .insert_resource(SpriteSheetHandles::default())
+            .add_asset::<SpriteSheet>()
+            .init_asset_loader::<XmlSheetLoader>()
+            .init_asset_loader::<CsvSheetLoader>()
+            .add_systems(PreStartup, load)
+            .add_systems(Update, register_sheets)
+            // After Update so sprites changed this frame are shown this frame
+            .add_systems(PostUpdate, resolve_named_sprites)
+            .register_type::<NamedSprite>();
     }
 }
 
-fn load(
-    asset_server: Res<AssetServer>,
-    mut atlases: ResMut<Assets<TextureAtlas>>,
+fn load(asset_server: Res<AssetServer>, mut handles: ResMut<SpriteSheetHandles>) {
+    let paths = match asset_server
+        .asset_io()
+        .read_directory(Path::new(SPRITES_DIR))
+    {
+        Ok(p) => p.collect::<Vec<PathBuf>>(),
+        Err(e) => {
+            error!("Could not read the sprites folder: {e}");
+            return;
+        }
+    };
+
+    // A hand written csv takes priority over the xml from TexturePacker
+    let is_sheet = |path: &PathBuf, ext: &str| path.extension().is_some_and(|e| e == ext);
+    for path in paths.iter() {
+        let has_csv = || {
+            paths
+                .iter()
+                .any(|p| is_sheet(p, "csv") && p.file_stem() == path.file_stem())
+        };
+        if is_sheet(path, "csv") || (is_sheet(path, "xml") && !has_csv()) {
+            handles.0.push(asset_server.load(path.clone()));
+        }
+    }
+}
+
+/// Adds sheets to [`SpriteSheets`] when they have loaded, and updates them when they are modified
+/// (the metadata file or the texture was changed while the game is running)
+fn register_sheets(
+    mut events: EventReader<AssetEvent<SpriteSheet>>,
     mut sheets: ResMut<SpriteSheets>,
+    mut sprites: Query<(&mut TextureAtlasSprite, &mut Handle<TextureAtlas>)>,
+    assets: Res<Assets<SpriteSheet>>,
+    asset_server: Res<AssetServer>,
 ) {
-    let png = "sprites/player_sheet.png";
-    let csv = "assets/sprites/player_sheet.csv";
-    let handle = asset_server.load(png);
-    let mut atlas = TextureAtlas::new_empty(handle, Vec2::new(561f32, 604f32));
-    let data = get_csv_data(csv).unwrap_or_else(|_| panic!(""));
-    let mut map = HashMap::new();
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        let (sheet, path) = match (assets.get(handle), asset_server.get_handle_path(handle)) {
+            (Some(sheet), Some(path)) => (sheet, path),
+            _ => continue,
+        };
+
+        let name = sheet_name(path.path());
+        if let Some(old) = sheets.0.get(&name) {
+            remap_sprites(old, sheet, &mut sprites);
+        }
+        sheets.0.insert(name, sheet.clone());
+    }
+}
+
+/// The order of the sprites in a sheet can change when it's reloaded, so every sprite using the
+/// old sheet is pointed to the sprite with the same name in the new one
+fn remap_sprites(
+    old: &SpriteSheet,
+    new: &SpriteSheet,
+    sprites: &mut Query<(&mut TextureAtlasSprite, &mut Handle<TextureAtlas>)>,
+) {
+    let old_names = old
+        .info
+        .iter()
+        .map(|(name, info)| (info.0, name))
+        .collect::<HashMap<usize, &String>>();
+
+    for (mut sprite, mut atlas) in sprites.iter_mut() {
+        if *atlas != old.atlas {
+            continue;
+        }
+        if *atlas != new.atlas {
+            *atlas = new.atlas.clone();
+        }
+
+        let name = match old_names.get(&sprite.index) {
+            Some(n) => n,
+            None => continue,
+        };
+        match new.info.get(*name) {
+            Some(info) if info.0 != sprite.index => sprite.index = info.0,
+            Some(_) => {}
+            None => warn!("The sprite {name} was removed from its sheet"),
+        }
+    }
+}
+
+/// Points sprites at their named sprite when the name changes, or when the sheets are updated
+fn resolve_named_sprites(
+    mut query: Query<(
+        Ref<NamedSprite>,
+        &mut TextureAtlasSprite,
+        &mut Handle<TextureAtlas>,
+    )>,
+    sheets: Res<SpriteSheets>,
+) {
+    for (named, mut sprite, mut atlas) in query.iter_mut() {
+        if !named.is_changed() && !sheets.is_changed() {
+            continue;
+        }
+        // The sheet might not have loaded yet
+        let sheet = match sheets.0.get(&named.sheet) {
+            Some(s) => s,
+            None => continue,
+        };
+        let idx = match sheet.info.get(&named.name) {
+            Some(info) => info.0,
+            None => {
+                warn!("{} has no sprite called {}", named.sheet, named.name);
+                continue;
+            }
+        };
 
-    for (i, (name, transform)) in data.iter().enumerate() {
-        map.insert(name.clone(), SpriteInfo(i));
-        let transform = transform.iter().map(|u| *u as f32).collect::<Vec<f32>>();
-        atlas.add_texture(Rect::new(
-            transform[0],
-            transform[1],
-            transform[0] + transform[2],
-            transform[1] + transform[3],
-        ));
+        if sprite.index != idx {
+            sprite.index = idx;
+        }
+        if *atlas != sheet.atlas {
+            *atlas = sheet.atlas.clone();
+        }
     }
-    let atlas_handle = atlases.add(atlas);
-    sheets.0.insert(
-        "player_sheet".to_string(),
-        SpriteSheet {
-            info: map,
-            atlas: atlas_handle,
-        },
-    );
+}
+
+/// `sprites/player_sheet.xml` is registered as `player_sheet`
+fn sheet_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
 }
 
 #[derive(Resource, Default)]
 pub struct SpriteSheets(pub HashMap<String, SpriteSheet>);
 
+/// Keeps the sheets loaded through the asset server alive
+#[derive(Resource, Default)]
+pub struct SpriteSheetHandles(pub Vec<Handle<SpriteSheet>>);
+
 /// To be used in a hashmap (HashMap<String (*the name*), SpriteSheet>)
-#[derive(Clone, Debug, PartialEq)]
+#[derive(TypeUuid, TypePath, Clone, Debug, PartialEq)]
+#[uuid = "b3a1e3f0-8c52-4d8e-a7f4-3e0c9d61f2a5"]
 pub struct SpriteSheet {
     pub info: HashMap<String, SpriteInfo>,
     pub atlas: Handle<TextureAtlas>,
 }
 
+/// Sets the atlas and index of the entity's [`TextureAtlasSprite`] to a sprite by name, so the
+/// entity needs a [`TextureAtlasSprite`] and a `Handle<TextureAtlas>` as well
+#[derive(Component, Clone, Debug, PartialEq, Reflect, Default)]
+pub struct NamedSprite {
+    pub sheet: String,
+    pub name: String,
+}
+
+impl NamedSprite {
+    pub fn new(sheet: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            sheet: sheet.into(),
+            name: name.into(),
+        }
+    }
+}
+
 /// To be used in a hashmap (HashMap<String (*the name*), SpriteInfo>)
 #[derive(Clone, Debug, PartialEq)]
 pub struct SpriteInfo(pub usize);
 
-fn get_csv_data(path: &str) -> Result<Vec<(String, [u32; 4])>, Box<dyn Error>> {
+/// Everything that can go wrong when loading a sprite sheet. Lines start at 1
+#[derive(Debug)]
+pub enum SpriteSheetError {
+    MissingFile(PathBuf),
+    InvalidNumber { line: u64, value: String },
+    WrongColumnCount { line: u64, count: usize },
+    MissingAttribute { line: u64, attribute: String },
+    OutOfBounds { line: u64, name: String, size: Vec2 },
+    DuplicateName { line: u64, name: String },
+    Csv(csv::Error),
+    Xml(roxmltree::Error),
+    Image(TextureError),
+    Io(AssetIoError),
+}
+
+impl std::fmt::Display for SpriteSheetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingFile(path) => write!(f, "missing file {}", path.display()),
+            Self::InvalidNumber { line, value } => {
+                write!(f, "line {line}: expected a whole number, but got \"{value}\"")
+            }
+            Self::WrongColumnCount { line, count } => write!(
+                f,
+                "line {line}: expected a name and 4 numbers (x, y, width, height), but got {count} numbers"
+            ),
+            Self::MissingAttribute { line, attribute } => {
+                write!(f, "line {line}: missing the attribute {attribute}")
+            }
+            Self::OutOfBounds { line, name, size } => write!(
+                f,
+                "line {line}: sprite {name} is outside of the {}x{} texture",
+                size.x, size.y
+            ),
+            Self::DuplicateName { line, name } => {
+                write!(f, "line {line}: there is already a sprite called {name}")
+            }
+            Self::Csv(e) => write!(f, "{e}"),
+            Self::Xml(e) => write!(f, "{e}"),
+            Self::Image(e) => write!(f, "{e}"),
+            Self::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for SpriteSheetError {}
+
+/// A sprite from a sheet's metadata file, before it's been checked against the texture
+struct SheetTexture {
+    name: String,
+    rect: Rect,
+    line: u64,
+}
+
+/// Reads the texture, checks the sprites against it and sets the [`SpriteSheet`] along with the
+/// `texture` and `atlas` labels
+async fn load_sheet<'a>(
+    load_context: &mut LoadContext<'a>,
+    image_path: PathBuf,
+    textures: Vec<SheetTexture>,
+) -> Result<(), SpriteSheetError> {
+    let image_bytes = match load_context.read_asset_bytes(&image_path).await {
+        Ok(b) => b,
+        Err(AssetIoError::NotFound(path)) => return Err(SpriteSheetError::MissingFile(path)),
+        Err(e) => return Err(SpriteSheetError::Io(e)),
+    };
+    let extension = image_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let image = Image::from_buffer(
+        &image_bytes,
+        ImageType::Extension(&extension),
+        CompressedImageFormats::NONE,
+        true,
+    )
+    .map_err(SpriteSheetError::Image)?;
+
+    let size = image.size();
+    let texture = load_context.set_labeled_asset("texture", LoadedAsset::new(image));
+    let mut atlas = TextureAtlas::new_empty(texture, size);
+    let mut map = HashMap::new();
+    for texture in textures {
+        if texture.rect.min.cmplt(Vec2::ZERO).any() || texture.rect.max.cmpgt(size).any() {
+            return Err(SpriteSheetError::OutOfBounds {
+                line: texture.line,
+                name: texture.name,
+                size,
+            });
+        }
+        if map.contains_key(&texture.name) {
+            return Err(SpriteSheetError::DuplicateName {
+                line: texture.line,
+                name: texture.name,
+            });
+        }
+        map.insert(texture.name, SpriteInfo(atlas.add_texture(texture.rect)));
+    }
+
+    let atlas = load_context.set_labeled_asset("atlas", LoadedAsset::new(atlas));
+    load_context.set_default_asset(LoadedAsset::new(SpriteSheet { info: map, atlas }));
+    Ok(())
+}
+
+/// Loads csv sheets where every row is `name, x, y, width, height`. The texture is the png with
+/// the same name
+#[derive(Default)]
+pub struct CsvSheetLoader;
+
+impl AssetLoader for CsvSheetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let textures = get_csv_data(bytes)?;
+            let image_path = load_context.path().with_extension("png");
+            Ok(load_sheet(load_context, image_path, textures).await?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["csv"]
+    }
+}
+
+fn get_csv_data(bytes: &[u8]) -> Result<Vec<SheetTexture>, SpriteSheetError> {
     let mut data = Vec::new();
 
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(false)
         .trim(csv::Trim::All)
-        .flexible(false)
-        .from_path(path)?;
-    for result in rdr.records().map(|r| {
-        let mut result = r?.iter().map(|s| s.to_string()).collect::<Vec<String>>();
-        let name = result.remove(0);
-        let transform = result
-            .iter()
-            .map(|d| d.parse::<u32>().unwrap())
-            .collect::<Vec<u32>>()
-            .try_into()
-            .unwrap_or_else(|v: Vec<u32>| {
-                panic!("Expected an array of length 4, but got {}", v.len())
+        .flexible(true)
+        .from_reader(bytes);
+    for record in rdr.records() {
+        let record = record.map_err(SpriteSheetError::Csv)?;
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        if record.len() != 5 {
+            return Err(SpriteSheetError::WrongColumnCount {
+                line,
+                count: record.len().saturating_sub(1),
             });
+        }
+
+        let mut numbers = [0f32; 4];
+        for (number, value) in numbers.iter_mut().zip(record.iter().skip(1)) {
+            *number = match value.parse::<u32>() {
+                Ok(n) => n as f32,
+                Err(_) => {
+                    return Err(SpriteSheetError::InvalidNumber {
+                        line,
+                        value: value.to_string(),
+                    })
+                }
+            };
+        }
+        let [x, y, width, height] = numbers;
 
-        Ok::<(String, [u32; 4]), Box<dyn Error>>((name, transform))
-    }) {
-        data.push(result?)
+        data.push(SheetTexture {
+            name: record[0].to_string(),
+            rect: Rect::new(x, y, x + width, y + height),
+            line,
+        });
     }
 
     Ok(data)
 }
+
+/// Loads TexturePacker xml sheets (`<TextureAtlas imagePath=".."><SubTexture name=".." x=".."
+/// y=".." width=".." height=".."/></TextureAtlas>`). The image is loaded as the `texture` label,
+/// the atlas as `atlas`, and sprite names don't include the file extension
+#[derive(Default)]
+pub struct XmlSheetLoader;
+
+impl AssetLoader for XmlSheetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let (image_path, textures) = get_xml_data(std::str::from_utf8(bytes)?)?;
+            let image_path = load_context
+                .path()
+                .parent()
+                .map(|p| p.join(&image_path))
+                .unwrap_or(image_path);
+            Ok(load_sheet(load_context, image_path, textures).await?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["xml"]
+    }
+}
+
+fn get_xml_data(text: &str) -> Result<(PathBuf, Vec<SheetTexture>), SpriteSheetError> {
+    let doc = roxmltree::Document::parse(text).map_err(SpriteSheetError::Xml)?;
+    let missing = |node: roxmltree::Node, attribute: &str| SpriteSheetError::MissingAttribute {
+        line: doc.text_pos_at(node.range().start).row as u64,
+        attribute: attribute.to_string(),
+    };
+
+    let root = doc.root_element();
+    let image_path = match root.attribute("imagePath") {
+        Some(p) => PathBuf::from(p),
+        None => return Err(missing(root, "imagePath")),
+    };
+
+    let mut textures = Vec::new();
+    for node in root.children().filter(|n| n.has_tag_name("SubTexture")) {
+        let line = doc.text_pos_at(node.range().start).row as u64;
+        let attr =
+            |name: &str| match node.attribute(name) {
+                Some(v) => v.parse::<u32>().map(|n| n as f32).map_err(|_| {
+                    SpriteSheetError::InvalidNumber {
+                        line,
+                        value: v.to_string(),
+                    }
+                }),
+                None => Err(missing(node, name)),
+            };
+        let name = match node.attribute("name") {
+            Some(n) => Path::new(n)
+                .with_extension("")
+                .to_string_lossy()
+                .to_string(),
+            None => return Err(missing(node, "name")),
+        };
+        let (x, y) = (attr("x")?, attr("y")?);
+        textures.push(SheetTexture {
+            name,
+            rect: Rect::new(x, y, x + attr("width")?, y + attr("height")?),
+            line,
+        });
+    }
+
+    Ok((image_path, textures))
+}
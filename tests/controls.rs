@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+use platformer::player::input::{
+    controls::{Controls, Slot},
+    InputAction,
+};
+
+#[test]
+fn binding_a_used_key_swaps_it() {
+    let mut controls = Controls::default();
+    controls
+        .bind_key(Slot::Action(InputAction::Jump), KeyCode::Z)
+        .unwrap();
+    assert_eq!(controls.jump, Some(KeyCode::Z));
+    assert_eq!(controls.grapple, Some(KeyCode::C));
+
+    controls.bind_key(Slot::RunLeft, KeyCode::Right).unwrap();
+    assert_eq!(controls.run_left, KeyCode::Right);
+    assert_eq!(controls.run_right, KeyCode::Left);
+}
+
+#[test]
+fn run_keys_stay_bound() {
+    let mut controls = Controls {
+        land: None,
+        ..Default::default()
+    };
+    assert!(controls
+        .bind_key(Slot::Action(InputAction::Land), KeyCode::Left)
+        .is_err());
+    assert_eq!(controls, Controls {
+        land: None,
+        ..Default::default()
+    });
+}
@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use platformer::{headless::Simulation, level::Level};
+
+/// A ramp going up to the right, and nothing else to stand on
+const RAMP_LEVEL: &str = r#"(
+    player_spawn: (0.0, 150.0),
+    polygons: [
+        (
+            name: "Ramp",
+            position: (0.0, 0.0),
+            vertices: [(-100.0, 0.0), (100.0, 0.0), (100.0, 50.0)],
+        ),
+    ],
+)"#;
+
+#[test]
+fn polygons_are_solid() {
+    let level = ron::from_str::<Level>(RAMP_LEVEL).unwrap();
+    let mut sim = Simulation::new();
+    sim.spawn_level(&level);
+
+    let ramp = sim
+        .app
+        .world
+        .query::<(&Name, &Collider)>()
+        .iter(&sim.app.world)
+        .any(|(name, _)| name.as_str() == "Ramp");
+    assert!(ramp, "the ramp has no collider");
+
+    sim.steps(120);
+    assert!(sim.position().y > 0f32, "the player fell through the ramp");
+}
@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use platformer::{
+    headless::{InputScript, Simulation},
+    player::{
+        input::InputAction,
+        state_machine::states::{GroundedState, InAirState, WallState},
+    },
+};
+
+/// The player stands at this height on [`floor`]
+const STANDING_Y: f32 = 35f32;
+/// Jump force squared over twice the gravity
+const JUMP_HEIGHT: f32 = 80f32;
+
+/// A simulation with a wide floor whose top is at y = 10, and the player standing on it
+fn floor() -> Simulation {
+    let mut sim = Simulation::new();
+    sim.spawn_solid(Vec2::ZERO, Vec2::new(2000f32, 20f32));
+    // The player starts out idle, in the air
+    let fell = sim.step_until(10, |sim| sim.get::<InAirState>().is_some());
+    assert!(fell.is_some(), "the player never fell");
+    let landed = sim.step_until(120, |sim| {
+        sim.get::<GroundedState>() == Some(GroundedState::Idle)
+    });
+    assert!(landed.is_some(), "the player never landed");
+    sim
+}
+
+fn max_height(positions: &[Vec2]) -> f32 {
+    positions.iter().map(|p| p.y).fold(f32::MIN, f32::max)
+}
+
+#[test]
+fn falls_onto_the_floor() {
+    let mut sim = floor();
+    assert!((sim.position().y - STANDING_Y).abs() < 1f32);
+    // Standing still, the velocity is only the gravity of one step
+    sim.steps(30);
+    assert!((sim.position().y - STANDING_Y).abs() < 1f32);
+    assert!(sim.velocity().y.abs() < 20f32);
+}
+
+#[test]
+fn holding_jump_reaches_the_jump_height() {
+    let mut sim = floor();
+    let start = sim.position().y;
+    let positions = sim.play(&InputScript::new().hold(InputAction::Jump, 60));
+    let height = max_height(&positions) - start;
+    assert!(
+        (height - JUMP_HEIGHT).abs() < JUMP_HEIGHT * 0.1f32,
+        "jumped {height} high"
+    );
+}
+
+#[test]
+fn tapping_jump_jumps_lower() {
+    let mut sim = floor();
+    let start = sim.position().y;
+    let tap = sim.play(&InputScript::new().hold(InputAction::Jump, 3).wait(60));
+    let height = max_height(&tap) - start;
+    assert!(height > 0f32, "didn't jump");
+    assert!(height < JUMP_HEIGHT * 0.5f32, "jumped {height} high");
+}
+
+#[test]
+fn lands_after_jumping() {
+    let mut sim = floor();
+    sim.play(&InputScript::new().hold(InputAction::Jump, 5));
+    assert_ne!(sim.get::<GroundedState>(), Some(GroundedState::Idle));
+    let landed = sim.step_until(120, |sim| {
+        sim.get::<GroundedState>() == Some(GroundedState::Idle)
+    });
+    assert!(landed.is_some());
+    assert!((sim.position().y - STANDING_Y).abs() < 1f32);
+}
+
+#[test]
+fn running_accelerates_to_the_max_speed() {
+    let mut sim = floor();
+    let start = sim.position().x;
+    sim.play(&InputScript::new().run(-1f32).wait(60));
+    assert!(sim.position().x < start);
+    // Acceleration stops before it would go over the max speed
+    let speed = -sim.velocity().x;
+    assert!((225f32..=250f32).contains(&speed), "running at {speed}");
+    assert_eq!(sim.get::<GroundedState>(), Some(GroundedState::Walking));
+}
+
+#[test]
+fn stops_after_running() {
+    let mut sim = floor();
+    sim.play(&InputScript::new().run(1f32).wait(30).run(0f32).wait(60));
+    assert_eq!(sim.velocity().x, 0f32);
+}
+
+#[test]
+fn walljump_pushes_away_from_the_wall() {
+    let mut sim = floor();
+    // A wall to the right of the player, taller than it can jump
+    sim.spawn_solid(Vec2::new(100f32, 500f32), Vec2::new(20f32, 1000f32));
+    sim.set_position(Vec2::new(60f32, 300f32));
+
+    sim.run(1f32);
+    let on_wall = sim.step_until(120, |sim| sim.get::<WallState>().is_some());
+    assert!(on_wall.is_some(), "the player never got onto the wall");
+    sim.run(0f32);
+
+    sim.press(InputAction::Jump);
+    let jumped = sim.step_until(10, |sim| sim.velocity().x < 0f32);
+    assert!(
+        jumped.is_some(),
+        "the walljump didn't push away from the wall"
+    );
+    assert!(sim.velocity().y > 0f32);
+}
+
+#[test]
+fn falling_is_capped_at_the_terminal_velocity() {
+    let mut sim = Simulation::new();
+    // Long enough to reach the terminal velocity of the default profile
+    sim.steps(120);
+    assert_eq!(sim.velocity().y, -1150f32);
+}
+
+#[test]
+fn the_same_input_moves_the_same_way() {
+    let script = InputScript::new()
+        .run(1f32)
+        .wait(20)
+        .hold(InputAction::Jump, 12)
+        .wait(10)
+        .run(-1f32)
+        .hold(InputAction::Jump, 4)
+        .hold(InputAction::Grapple, 30)
+        .wait(20)
+        .run(0f32)
+        .wait(60);
+    let play = || {
+        let mut sim = floor();
+        // A wall to run into, and a point above the floor to grapple
+        sim.spawn_solid(Vec2::new(150f32, 200f32), Vec2::new(20f32, 400f32));
+        sim.spawn_grapple_point(Vec2::new(0f32, 200f32));
+        sim.play(&script)
+    };
+
+    let first = play();
+    let second = play();
+    assert_eq!(first.len(), second.len());
+    for (frame, (a, b)) in first.iter().zip(second.iter()).enumerate() {
+        assert_eq!(a, b, "the positions differ after {frame} frames");
+    }
+}
@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+use platformer::{
+    headless::{InputScript, Simulation},
+    player::{
+        input::InputAction,
+        movement::jumper::Jumper,
+        replay::{load_player_state, save_player_state},
+        state_machine::states::{GroundedState, InAirState},
+    },
+};
+
+#[test]
+fn player_state_round_trips() {
+    let mut sim = Simulation::new();
+    sim.spawn_solid(Vec2::ZERO, Vec2::new(2000f32, 20f32));
+    // The player starts out idle, in the air
+    let fell = sim.step_until(10, |sim| sim.get::<InAirState>().is_some());
+    assert!(fell.is_some(), "the player never fell");
+    let landed = sim.step_until(120, |sim| sim.get::<GroundedState>().is_some());
+    assert!(landed.is_some(), "the player never landed");
+
+    // Mid jump, with the jump buffer and coyote time running
+    sim.play(&InputScript::new().hold(InputAction::Jump, 3));
+    assert_eq!(sim.get::<InAirState>(), Some(InAirState::Rising));
+    let player = sim.player();
+    let jumper = sim.get::<Jumper>().unwrap();
+    let state = save_player_state(&sim.app.world, player).unwrap();
+
+    sim.steps(120);
+    assert!(sim.get::<GroundedState>().is_some(), "the player never landed");
+    assert_ne!(sim.get::<Jumper>(), Some(jumper.clone()));
+
+    load_player_state(&mut sim.app.world, player, &state).unwrap();
+    assert_eq!(sim.get::<GroundedState>(), None);
+    assert_eq!(sim.get::<InAirState>(), Some(InAirState::Rising));
+    assert_eq!(sim.get::<Jumper>(), Some(jumper));
+}
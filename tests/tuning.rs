@@ -0,0 +1,85 @@
+use platformer::player::movement::{
+    grappler::Grappler, horizontal_movement::HorizontalMovement, jumper::Jumper, lander::Lander,
+    slingshot::Slingshot, terminal_velocity::TerminalVelocity, wall_movement::WallMovement,
+};
+
+#[test]
+fn builders_default_to_valid_tuning() {
+    assert!(Jumper::builder().build().is_ok());
+    assert!(WallMovement::builder().build().is_ok());
+    assert!(Slingshot::builder().build().is_ok());
+    assert!(Grappler::builder().build().is_ok());
+    assert!(TerminalVelocity::builder().build().is_ok());
+    assert!(HorizontalMovement::builder().build().is_ok());
+    assert!(Lander::builder().build().is_ok());
+}
+
+#[test]
+fn builders_report_every_problem() {
+    let err = Grappler::builder()
+        .min_desired(250f32)
+        .max_desired(100f32)
+        .grapple_buffer(-1f32)
+        .build()
+        .unwrap_err();
+    assert_eq!(err.component, "Grappler");
+    assert_eq!(err.problems.len(), 2, "{err}");
+
+    let err = Jumper::builder().release_multi(1.5f32).build().unwrap_err();
+    assert!(err.to_string().contains("release_multi"), "{err}");
+
+    let err = WallMovement::builder()
+        .walljump_y_range(20f32, -20f32)
+        .coyote_time(f32::NAN)
+        .build()
+        .unwrap_err();
+    assert_eq!(err.problems.len(), 2, "{err}");
+
+    let err = HorizontalMovement::builder()
+        .acceleration_time(0f32)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("acceleration_time"), "{err}");
+
+    let err = Lander::builder().min_land_vel(100f32).build().unwrap_err();
+    assert!(err.to_string().contains("min_land_vel"), "{err}");
+}
+
+/// Infinite timers panic
+#[test]
+fn timers_have_to_be_finite() {
+    let err = Jumper::builder()
+        .coyote_time(f32::INFINITY)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("coyote_time"), "{err}");
+    assert!(Slingshot::builder().delay_time(f32::INFINITY).build().is_err());
+}
+
+#[test]
+fn components_round_trip_through_ron() {
+    let jumper = Jumper::builder()
+        .jump_force(500f32)
+        .coyote_time(0.1f32)
+        .build()
+        .unwrap();
+    let ron = ron::to_string(&jumper).unwrap();
+    let read = ron::from_str::<Jumper>(&ron).unwrap();
+    assert_eq!(read.jump_force, 500f32);
+    assert_eq!(ron::to_string(&read).unwrap(), ron);
+}
+
+/// Missing values are the defaults of the builders
+#[test]
+fn deserializing_validates_the_tuning() {
+    assert!(ron::from_str::<Slingshot>("(range: 0.0)").is_err());
+    assert!(ron::from_str::<WallMovement>("(walljump_force: (450.0, -1.0))").is_err());
+    assert_eq!(
+        ron::from_str::<Grappler>("(max_desired: 150.0)").unwrap(),
+        Grappler::builder().max_desired(150f32).build().unwrap()
+    );
+    assert_eq!(
+        ron::from_str::<TerminalVelocity>("()").unwrap().base_val,
+        -1150f32
+    );
+}